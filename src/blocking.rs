@@ -0,0 +1,280 @@
+use std::borrow::Borrow;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+use ::curl::easy::Easy;
+use serde::de::DeserializeOwned;
+
+use crate::body::ThreadWaker;
+use crate::json::parse_json;
+use crate::middleware::{Attempt, Endpoint};
+use crate::transport::{collect_response, ResponseData};
+use crate::{
+    transfer_error, BoxFuture, CookieJar, Error, ErrorKind, Headers, Interceptor, Middleware,
+    Multipart, NoInterceptor, ProxyConfig, RedirectPolicy, Request, Response, RetryPolicy,
+    Timeouts, TlsConfig, Transport, UrlParseError,
+};
+
+pub struct HttpClient<I: Interceptor> {
+    inner: crate::HttpClient<I>,
+}
+
+impl HttpClient<NoInterceptor> {
+    pub fn new<U>(base_url: U) -> Result<HttpClient<NoInterceptor>, UrlParseError>
+    where
+        U: AsRef<str>,
+    {
+        let inner = crate::HttpClient::new(base_url)?;
+        Ok(HttpClient { inner })
+    }
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub fn with_interceptor<O>(self, interceptor: O) -> HttpClient<O>
+    where
+        O: Interceptor,
+    {
+        HttpClient {
+            inner: self.inner.with_interceptor(interceptor),
+        }
+    }
+
+    pub fn set_default_headers<H, K, V>(&mut self, headers: H)
+    where
+        H: IntoIterator,
+        H::Item: Borrow<(K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        self.inner.set_default_headers(headers)
+    }
+
+    pub fn default_headers_mut(&mut self) -> &mut Headers {
+        self.inner.default_headers_mut()
+    }
+
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.inner.set_connect_timeout(timeout)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.set_timeout(timeout)
+    }
+
+    pub fn set_low_speed_timeout(&mut self, bytes_per_second: u32, time: Duration) {
+        self.inner.set_low_speed_timeout(bytes_per_second, time)
+    }
+
+    pub fn set_retry_policy<P: RetryPolicy + 'static>(&mut self, policy: P) {
+        self.inner.set_retry_policy(policy)
+    }
+
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.inner.add_middleware(middleware)
+    }
+
+    pub fn set_transport<T: Transport + 'static>(&mut self, transport: T) {
+        self.inner.set_transport(transport)
+    }
+
+    pub fn set_redirect_policy(&mut self, policy: RedirectPolicy) {
+        self.inner.set_redirect_policy(policy)
+    }
+
+    pub fn set_proxy(&mut self, proxy: ProxyConfig) {
+        self.inner.set_proxy(proxy)
+    }
+
+    pub fn set_tls_config(&mut self, tls: TlsConfig) {
+        self.inner.set_tls_config(tls)
+    }
+
+    pub fn set_cookie_jar(&mut self, cookie_jar: CookieJar) {
+        self.inner.set_cookie_jar(cookie_jar)
+    }
+
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.inner.cookie_jar()
+    }
+
+    pub fn new_request<P>(&self, path: P) -> Request
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.inner.new_request(path)
+    }
+
+    pub fn new_request_with_params<P, I, K, V>(&self, path: P, params: I) -> Request
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.inner.new_request_with_params(path, params)
+    }
+
+    pub fn new_request_with_url<U>(&self, url: U) -> Result<Request, UrlParseError>
+    where
+        U: AsRef<str>,
+    {
+        self.inner.new_request_with_url(url)
+    }
+
+    pub fn get<R, P>(&self, path: P) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.perform_request(self.new_request(path), parse_json)
+    }
+
+    pub fn get_with_params<R, P, I, K, V>(&self, path: P, params: I) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.perform_request(self.new_request_with_params(path, params), parse_json)
+    }
+
+    pub fn perform_request<R, P>(&self, mut request: Request, parse: P) -> Result<R, Error>
+    where
+        P: FnOnce(Request, Response) -> Result<R, Error>,
+    {
+        match self.perform(&mut request) {
+            Ok(response) => parse(request, response),
+            Err(kind) => Err(Error {
+                request: Box::new(request),
+                kind,
+            }),
+        }
+    }
+
+    // the middleware chain is async, but everything it awaits here completes on this thread
+    fn perform(&self, request: &mut Request) -> Result<Response, ErrorKind> {
+        block_on(self.inner.dispatch_to(request, self))
+    }
+}
+
+struct Blocking<'c, X: Interceptor> {
+    client: &'c crate::HttpClient<X>,
+    curl: Option<Performing>,
+}
+
+struct Performing {
+    easy: Easy,
+    timeouts: Timeouts,
+    data: Arc<Mutex<ResponseData>>,
+}
+
+impl<X: Interceptor> Attempt for Blocking<'_, X> {
+    fn prepare(&mut self, request: &mut Request) -> Result<(), ErrorKind> {
+        let client = self.client;
+
+        if client.transport.is_some() {
+            return client.apply_default_headers(request);
+        }
+
+        let (mut easy, timeouts) = client.prepare_easy(request, &Arc::default())?;
+
+        let body = request
+            .form
+            .as_ref()
+            .map(Multipart::body)
+            .or(request.body.as_ref());
+        if let Some(body) = body {
+            body.configure_blocking(&mut easy);
+        }
+
+        let data = Arc::new(Mutex::new(ResponseData::default()));
+        collect_response(&mut easy, &data);
+
+        self.curl = Some(Performing {
+            easy,
+            timeouts,
+            data,
+        });
+        Ok(())
+    }
+
+    fn perform<'a>(
+        &'a mut self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        let Some(Performing {
+            easy,
+            timeouts,
+            data,
+        }) = &mut self.curl
+        else {
+            let transport = self.client.transport.as_ref().unwrap();
+            return transport.send(request);
+        };
+
+        *data.lock().unwrap() = ResponseData::default();
+
+        let outcome = match easy.perform() {
+            Ok(()) => {
+                let ResponseData { body, headers } = std::mem::take(&mut *data.lock().unwrap());
+                Ok(Response::from_raw(
+                    easy.response_code().unwrap(),
+                    &headers,
+                    body,
+                ))
+            }
+            Err(err) => Err(transfer_error(err, easy, timeouts)),
+        };
+
+        Box::pin(async move { outcome })
+    }
+
+    fn pause(&self, delay: Duration) -> BoxFuture<'_, ()> {
+        thread::sleep(delay);
+        Box::pin(async {})
+    }
+}
+
+impl<X: Interceptor> Endpoint for HttpClient<X> {
+    fn send<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let mut blocking = Blocking {
+                client: &self.inner,
+                curl: None,
+            };
+
+            self.inner.send_hops(request, &mut blocking).await
+        })
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = pin!(future);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
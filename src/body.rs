@@ -0,0 +1,486 @@
+use std::fmt;
+use std::future::poll_fn;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use ::curl::easy::{Easy, ReadError, SeekResult};
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use crate::driver::Resume;
+
+pub struct Body {
+    kind: Kind,
+}
+
+enum Kind {
+    Bytes(Vec<u8>),
+    Streamed {
+        source: Arc<Mutex<Streamed>>,
+        length: Option<u64>,
+    },
+}
+
+struct Streamed {
+    source: Box<dyn Source>,
+    started: bool,
+}
+
+pub(crate) trait Source: Send {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+
+    fn rewind(&mut self) -> bool {
+        false
+    }
+
+    // async sources may need the caller's runtime, so they're polled by the task awaiting
+    // the transfer rather than on the driver thread
+    fn is_async(&self) -> bool {
+        false
+    }
+}
+
+const PUMPED: usize = 64 * 1024;
+
+pub(crate) struct Pump {
+    source: Arc<Mutex<Streamed>>,
+    pipe: Mutex<Pipe>,
+}
+
+#[derive(Default)]
+struct Pipe {
+    buffer: Vec<u8>,
+    done: bool,
+    failed: bool,
+    // curl is paused until the buffer has something for it
+    paused: bool,
+    waker: Option<Waker>,
+}
+
+impl Body {
+    pub fn from_reader<R>(reader: R, length: Option<u64>) -> Body
+    where
+        R: Read + Send + 'static,
+    {
+        Body::streamed(ReaderSource(reader), length)
+    }
+
+    pub fn from_seekable<R>(mut reader: R, length: Option<u64>) -> io::Result<Body>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let start = reader.stream_position()?;
+        Ok(Body::streamed(SeekableSource { reader, start }, length))
+    }
+
+    pub fn from_async_reader<R>(reader: R, length: Option<u64>) -> Body
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        Body::streamed(AsyncReaderSource(reader), length)
+    }
+
+    pub fn from_stream<S>(stream: S, length: Option<u64>) -> Body
+    where
+        S: Stream<Item = io::Result<Bytes>> + Unpin + Send + 'static,
+    {
+        Body::streamed(
+            StreamSource {
+                stream,
+                pending: Bytes::new(),
+            },
+            length,
+        )
+    }
+
+    pub(crate) fn streamed<S: Source + 'static>(source: S, length: Option<u64>) -> Body {
+        Body {
+            kind: Kind::Streamed {
+                source: Arc::new(Mutex::new(Streamed {
+                    source: Box::new(source),
+                    started: false,
+                })),
+                length,
+            },
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.kind {
+            Kind::Bytes(bytes) => Some(bytes),
+            Kind::Streamed { .. } => None,
+        }
+    }
+
+    pub fn len(&self) -> Option<u64> {
+        match &self.kind {
+            Kind::Bytes(bytes) => Some(bytes.len() as u64),
+            Kind::Streamed { length, .. } => *length,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    pub fn is_streamed(&self) -> bool {
+        matches!(self.kind, Kind::Streamed { .. })
+    }
+
+    pub(crate) fn share(&self) -> Body {
+        let kind = match &self.kind {
+            Kind::Bytes(bytes) => Kind::Bytes(bytes.clone()),
+            Kind::Streamed { source, length } => Kind::Streamed {
+                source: source.clone(),
+                length: *length,
+            },
+        };
+
+        Body { kind }
+    }
+
+    pub(crate) fn into_source(self) -> Box<dyn Source> {
+        match self.kind {
+            Kind::Bytes(bytes) => Box::new(SeekableSource {
+                reader: io::Cursor::new(bytes),
+                start: 0,
+            }),
+            Kind::Streamed { source, .. } => Box::new(source),
+        }
+    }
+
+    // polled with the caller's context, so async sources can be fed by the same executor
+    pub(crate) async fn read_to_end(&self) -> io::Result<Vec<u8>> {
+        let source = match &self.kind {
+            Kind::Bytes(bytes) => return Ok(bytes.clone()),
+            Kind::Streamed { source, .. } => source,
+        };
+
+        let mut bytes = vec![];
+        let mut buf = [0; 16 * 1024];
+
+        loop {
+            match poll_fn(|cx| source.lock().unwrap().poll_read(cx, &mut buf)).await? {
+                0 => return Ok(bytes),
+                read => bytes.extend_from_slice(&buf[..read]),
+            }
+        }
+    }
+
+    pub(crate) fn rewind(&self) -> bool {
+        match &self.kind {
+            Kind::Bytes(_) => true,
+            Kind::Streamed { source, .. } => source.lock().unwrap().rewind(),
+        }
+    }
+
+    pub(crate) fn configure(&self, easy: &mut Easy, resume: &Arc<Resume>) {
+        let (source, length) = match &self.kind {
+            Kind::Bytes(bytes) => {
+                easy.post_field_size(bytes.len() as u64).unwrap();
+                easy.post_fields_copy(bytes).unwrap();
+                return;
+            }
+            Kind::Streamed { source, length } => (source, length),
+        };
+
+        easy.post(true).unwrap();
+
+        if let Some(length) = length {
+            easy.post_field_size(*length).unwrap();
+        }
+
+        if source.lock().unwrap().source.is_async() {
+            let pump = Arc::new(Pump {
+                source: source.clone(),
+                pipe: Mutex::default(),
+            });
+            resume.set_pump(pump.clone());
+
+            let reader = pump.clone();
+            easy.read_function(move |buf| reader.read(buf)).unwrap();
+
+            easy.seek_function(move |whence| match whence {
+                SeekFrom::Start(0) if pump.rewind() => SeekResult::Ok,
+                _ => SeekResult::CantSeek,
+            })
+            .unwrap();
+
+            return;
+        }
+
+        let reader = source.clone();
+        let waker = Waker::from(resume.clone());
+        easy.read_function(move |buf| {
+            let mut cx = Context::from_waker(&waker);
+
+            match reader.lock().unwrap().poll_read(&mut cx, buf) {
+                Poll::Ready(Ok(read)) => Ok(read),
+                Poll::Ready(Err(_)) => Err(ReadError::Abort),
+                Poll::Pending => Err(ReadError::Pause),
+            }
+        })
+        .unwrap();
+
+        let seeker = source.clone();
+        easy.seek_function(move |whence| match whence {
+            SeekFrom::Start(0) if seeker.lock().unwrap().rewind() => SeekResult::Ok,
+            _ => SeekResult::CantSeek,
+        })
+        .unwrap();
+    }
+
+    pub(crate) fn configure_blocking(&self, easy: &mut Easy) {
+        if let Kind::Streamed { source, .. } = &self.kind {
+            let reader = source.clone();
+            easy.read_function(move |buf| {
+                read_blocking(&reader, buf).map_err(|_| ReadError::Abort)
+            })
+            .unwrap();
+        }
+    }
+}
+
+fn read_blocking(source: &Arc<Mutex<Streamed>>, buf: &mut [u8]) -> io::Result<usize> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut source = source.lock().unwrap();
+
+    loop {
+        match source.poll_read(&mut cx, buf) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+impl Streamed {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.started = true;
+        self.source.poll_read(cx, buf)
+    }
+
+    fn rewind(&mut self) -> bool {
+        if !self.started {
+            return true;
+        }
+
+        let rewound = self.source.rewind();
+        self.started = !rewound;
+        rewound
+    }
+}
+
+impl Pump {
+    // fills the buffer up to PUMPED bytes with the caller's context, which the source
+    // wakes once it has more
+    pub(crate) fn poll(&self, cx: &mut Context<'_>, resume: &Resume) {
+        let mut pipe = self.pipe.lock().unwrap();
+        pipe.waker = Some(cx.waker().clone());
+
+        let mut filled = false;
+
+        while !pipe.done && pipe.buffer.len() < PUMPED {
+            let start = pipe.buffer.len();
+            pipe.buffer.resize(PUMPED, 0);
+
+            let polled = self
+                .source
+                .lock()
+                .unwrap()
+                .poll_read(cx, &mut pipe.buffer[start..]);
+
+            match polled {
+                Poll::Ready(Ok(read)) => {
+                    pipe.buffer.truncate(start + read);
+                    pipe.done = read == 0;
+                }
+                Poll::Ready(Err(_)) => {
+                    pipe.buffer.truncate(start);
+                    pipe.done = true;
+                    pipe.failed = true;
+                }
+                Poll::Pending => {
+                    pipe.buffer.truncate(start);
+                    break;
+                }
+            }
+
+            filled = true;
+        }
+
+        if filled && std::mem::take(&mut pipe.paused) {
+            resume.request();
+        }
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<usize, ReadError> {
+        let mut pipe = self.pipe.lock().unwrap();
+
+        if pipe.buffer.is_empty() {
+            if pipe.failed {
+                return Err(ReadError::Abort);
+            }
+
+            if pipe.done {
+                return Ok(0);
+            }
+
+            pipe.paused = true;
+            return Err(ReadError::Pause);
+        }
+
+        let read = buf.len().min(pipe.buffer.len());
+        buf[..read].copy_from_slice(&pipe.buffer[..read]);
+        pipe.buffer.drain(..read);
+
+        // there's room again, so the task polls the source for more
+        if let Some(waker) = pipe.waker.take() {
+            waker.wake();
+        }
+
+        Ok(read)
+    }
+
+    fn rewind(&self) -> bool {
+        let mut pipe = self.pipe.lock().unwrap();
+
+        if !self.source.lock().unwrap().rewind() {
+            return false;
+        }
+
+        let waker = pipe.waker.take();
+        *pipe = Pipe {
+            waker,
+            ..Default::default()
+        };
+        true
+    }
+}
+
+impl Source for Arc<Mutex<Streamed>> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.lock().unwrap().poll_read(cx, buf)
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.lock().unwrap().rewind()
+    }
+
+    fn is_async(&self) -> bool {
+        self.lock().unwrap().source.is_async()
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Body {
+        Body {
+            kind: Kind::Bytes(bytes),
+        }
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(bytes: &[u8]) -> Body {
+        Body::from(bytes.to_vec())
+    }
+}
+
+impl From<String> for Body {
+    fn from(string: String) -> Body {
+        Body::from(string.into_bytes())
+    }
+}
+
+impl From<&str> for Body {
+    fn from(string: &str) -> Body {
+        Body::from(string.as_bytes())
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.kind, self.len()) {
+            (Kind::Bytes(bytes), _) => write!(f, "{} bytes", bytes.len()),
+            (Kind::Streamed { .. }, Some(length)) => write!(f, "streamed {} bytes", length),
+            (Kind::Streamed { .. }, None) => write!(f, "streamed"),
+        }
+    }
+}
+
+pub(crate) struct ThreadWaker(pub(crate) Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+struct ReaderSource<R>(R);
+
+impl<R: Read + Send> Source for ReaderSource<R> {
+    fn poll_read(&mut self, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.0.read(buf))
+    }
+}
+
+struct SeekableSource<R> {
+    reader: R,
+    start: u64,
+}
+
+impl<R: Read + Seek + Send> Source for SeekableSource<R> {
+    fn poll_read(&mut self, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.reader.read(buf))
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.reader.seek(SeekFrom::Start(self.start)).is_ok()
+    }
+}
+
+struct AsyncReaderSource<R>(R);
+
+impl<R: AsyncRead + Unpin + Send> Source for AsyncReaderSource<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+
+    fn is_async(&self) -> bool {
+        true
+    }
+}
+
+struct StreamSource<S> {
+    stream: S,
+    pending: Bytes,
+}
+
+impl<S> Source for StreamSource<S>
+where
+    S: Stream<Item = io::Result<Bytes>> + Unpin + Send,
+{
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        while self.pending.is_empty() {
+            match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => self.pending = chunk,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(err)),
+                Poll::Ready(None) => return Poll::Ready(Ok(0)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let read = buf.len().min(self.pending.len());
+        buf[..read].copy_from_slice(&self.pending.split_to(read));
+        Poll::Ready(Ok(read))
+    }
+
+    fn is_async(&self) -> bool {
+        true
+    }
+}
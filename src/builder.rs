@@ -0,0 +1,215 @@
+use std::borrow::Borrow;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+
+use crate::json::parse_json;
+use crate::{
+    parse_void, Body, Error, HttpClient, HttpMethod, Interceptor, Multipart, Progress,
+    RedirectPolicy, Request, Response, RetryPolicy, StreamingResponse,
+};
+
+pub struct RequestBuilder<'a, X: Interceptor> {
+    client: &'a HttpClient<X>,
+    request: Request,
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub fn request<P>(&self, method: HttpMethod, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        let mut request = self.new_request(path);
+        request.set_method(method);
+
+        RequestBuilder {
+            client: self,
+            request,
+        }
+    }
+
+    // get is the JSON shortcut, so the GET builder goes by another name
+    pub fn fetch<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Get, path)
+    }
+
+    pub fn post<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Post, path)
+    }
+
+    pub fn put<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Put, path)
+    }
+
+    pub fn patch<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Patch, path)
+    }
+
+    pub fn delete<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Delete, path)
+    }
+
+    pub fn head<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Head, path)
+    }
+
+    pub fn options<P>(&self, path: P) -> RequestBuilder<'_, X>
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        self.request(HttpMethod::Options, path)
+    }
+}
+
+impl<'a, X: Interceptor> RequestBuilder<'a, X> {
+    pub fn query<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.request.url.query_pairs_mut().extend_pairs(params);
+        self
+    }
+
+    pub fn header<H: ToString, V: ToString>(mut self, header: H, value: V) -> Self {
+        self.request.set_header(header, value);
+        self
+    }
+
+    pub fn unset_header<H: ToString>(mut self, header: H) -> Self {
+        self.request.unset_header(header);
+        self
+    }
+
+    pub fn body<B: Into<Body>>(mut self, body: B) -> Self {
+        self.request.set_body(body);
+        self
+    }
+
+    pub fn json_body<J: serde::Serialize>(mut self, json: &J) -> Self {
+        self.request.set_json_body(json);
+        self
+    }
+
+    pub fn form<I, K, V>(mut self, form: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        self.request.set_form(form);
+        self
+    }
+
+    pub fn multipart(mut self, multipart: Multipart) -> Self {
+        self.request.set_multipart(multipart);
+        self
+    }
+
+    pub fn urlencoded<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<(K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.request.set_urlencoded_params(params);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.request.set_timeout(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.request.set_connect_timeout(timeout);
+        self
+    }
+
+    pub fn retry_policy<P: RetryPolicy + 'static>(mut self, policy: P) -> Self {
+        self.request.set_retry_policy(policy);
+        self
+    }
+
+    pub fn redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.request.set_redirect_policy(policy);
+        self
+    }
+
+    pub fn progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.request.set_progress(progress);
+        self
+    }
+
+    pub fn build(self) -> Request {
+        self.request
+    }
+
+    pub async fn send(self) -> Result<Response, Error> {
+        self.client
+            .perform_request(self.request, |_, res| Ok(res))
+            .await
+    }
+
+    pub async fn stream(self) -> Result<StreamingResponse, Error> {
+        self.client.perform_streaming(self.request).await
+    }
+
+    pub async fn json<R: DeserializeOwned + Send + 'static>(self) -> Result<R, Error> {
+        self.client.perform_request(self.request, parse_json).await
+    }
+
+    pub async fn text(self) -> Result<String, Error> {
+        let body = self.bytes().await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    pub async fn bytes(self) -> Result<Vec<u8>, Error> {
+        self.client
+            .perform_request(self.request, |req, res| {
+                if res.status_code >= 200 && res.status_code < 300 {
+                    Ok(res.body)
+                } else {
+                    Err((req, res).into())
+                }
+            })
+            .await
+    }
+
+    pub async fn void(self) -> Result<(), Error> {
+        self.client.perform_request(self.request, parse_void).await
+    }
+}
@@ -0,0 +1,552 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BoxFuture, ErrorKind, Headers, HttpMethod, Request, Response, Transport};
+
+#[derive(Clone)]
+pub struct Cassette {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    recorder: Option<Arc<dyn Transport>>,
+    config: CassetteConfig,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    played: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchRules {
+    pub method: bool,
+    pub url: bool,
+    pub headers: bool,
+    pub body: bool,
+}
+
+impl MatchRules {
+    pub fn strict() -> MatchRules {
+        MatchRules {
+            method: true,
+            url: true,
+            headers: true,
+            body: true,
+        }
+    }
+
+    pub fn lenient() -> MatchRules {
+        MatchRules {
+            method: true,
+            url: true,
+            headers: false,
+            body: false,
+        }
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> MatchRules {
+        MatchRules::strict()
+    }
+}
+
+// credentials are kept out of the file, and matched by name only
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CassetteConfig {
+    pub matching: MatchRules,
+    pub redacted_headers: Vec<String>,
+}
+
+impl CassetteConfig {
+    pub fn new() -> CassetteConfig {
+        CassetteConfig::default()
+    }
+
+    pub fn set_matching(&mut self, rules: MatchRules) {
+        self.matching = rules
+    }
+
+    pub fn redact_header<N: ToString>(&mut self, name: N) {
+        self.redacted_headers.push(name.to_string())
+    }
+
+    fn is_redacted(&self, name: &str) -> bool {
+        self.redacted_headers
+            .iter()
+            .any(|redacted| redacted.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for CassetteConfig {
+    fn default() -> CassetteConfig {
+        CassetteConfig {
+            matching: MatchRules::default(),
+            redacted_headers: vec![
+                "Authorization".to_string(),
+                "Cookie".to_string(),
+                "Proxy-Authorization".to_string(),
+                "Set-Cookie".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<RecordedBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status_code: u32,
+    version: String,
+    reason: String,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordedBody {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Cassette {
+    pub fn record<P: AsRef<Path>, T: Transport + 'static>(path: P, transport: T) -> Cassette {
+        Cassette::record_with(path, transport, CassetteConfig::default())
+    }
+
+    pub fn record_with<P: AsRef<Path>, T: Transport + 'static>(
+        path: P,
+        transport: T,
+        config: CassetteConfig,
+    ) -> Cassette {
+        Cassette::new(
+            path.as_ref(),
+            Some(Arc::new(transport)),
+            config,
+            State::default(),
+        )
+    }
+
+    pub fn replay<P: AsRef<Path>>(path: P) -> io::Result<Cassette> {
+        Cassette::replay_with(path, CassetteConfig::default())
+    }
+
+    pub fn replay_with<P: AsRef<Path>>(path: P, config: CassetteConfig) -> io::Result<Cassette> {
+        let state = State::load(path.as_ref())?;
+        Ok(Cassette::new(path.as_ref(), None, config, state))
+    }
+
+    pub fn once<P: AsRef<Path>, T: Transport + 'static>(
+        path: P,
+        transport: T,
+    ) -> io::Result<Cassette> {
+        Cassette::once_with(path, transport, CassetteConfig::default())
+    }
+
+    pub fn once_with<P: AsRef<Path>, T: Transport + 'static>(
+        path: P,
+        transport: T,
+        config: CassetteConfig,
+    ) -> io::Result<Cassette> {
+        if path.as_ref().exists() {
+            Cassette::replay_with(path, config)
+        } else {
+            Ok(Cassette::record_with(path, transport, config))
+        }
+    }
+
+    fn new(
+        path: &Path,
+        recorder: Option<Arc<dyn Transport>>,
+        config: CassetteConfig,
+        state: State,
+    ) -> Cassette {
+        Cassette {
+            inner: Arc::new(Inner {
+                path: path.to_path_buf(),
+                recorder,
+                config,
+                state: Mutex::new(state),
+            }),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.inner.recorder.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.state.lock().unwrap().interactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    async fn record_interaction(
+        &self,
+        transport: &dyn Transport,
+        request: &Request,
+    ) -> Result<Response, ErrorKind> {
+        let request = request.try_copy().await.map_err(ErrorKind::Io)?;
+        let recorded = RecordedRequest::from_request(&request, &self.inner.config)
+            .await
+            .map_err(ErrorKind::Io)?;
+        let response = transport.send(&request).await?;
+
+        let interaction = Interaction {
+            request: recorded,
+            response: RecordedResponse::from_response(&response, &self.inner.config),
+        };
+
+        let mut state = self.inner.state.lock().unwrap();
+        state.interactions.push(interaction);
+        state.played.push(true);
+        state.save(&self.inner.path).map_err(ErrorKind::Io)?;
+
+        Ok(response)
+    }
+
+    async fn replay_interaction(&self, request: &Request) -> Result<Response, ErrorKind> {
+        let recorded = RecordedRequest::from_request(request, &self.inner.config)
+            .await
+            .map_err(ErrorKind::Io)?;
+
+        let mut state = self.inner.state.lock().unwrap();
+        let State {
+            interactions,
+            played,
+        } = &mut *state;
+
+        let found = interactions
+            .iter()
+            .zip(played.iter_mut())
+            .find(|(interaction, played)| {
+                !**played
+                    && interaction
+                        .request
+                        .matches(&recorded, &self.inner.config.matching)
+            });
+
+        match found {
+            Some((interaction, played)) => {
+                *played = true;
+                Ok(interaction.response.to_response())
+            }
+            None => Err(ErrorKind::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "no recorded interaction for {} {} in {}",
+                    request.method,
+                    request.url,
+                    self.inner.path.display()
+                ),
+            ))),
+        }
+    }
+}
+
+impl Transport for Cassette {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            match &self.inner.recorder {
+                Some(transport) => self.record_interaction(transport.as_ref(), request).await,
+                None => self.replay_interaction(request).await,
+            }
+        })
+    }
+}
+
+impl State {
+    fn load(path: &Path) -> io::Result<State> {
+        let tape: Tape = serde_json::from_slice(&fs::read(path)?)?;
+
+        Ok(State {
+            played: vec![false; tape.interactions.len()],
+            interactions: tape.interactions,
+        })
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let tape = Tape {
+            interactions: self.interactions.clone(),
+        };
+
+        fs::write(path, serde_json::to_vec_pretty(&tape)?)
+    }
+}
+
+impl RecordedRequest {
+    async fn from_request(
+        request: &Request,
+        config: &CassetteConfig,
+    ) -> io::Result<RecordedRequest> {
+        Ok(RecordedRequest {
+            method: request.method.to_string(),
+            url: request.url.to_string(),
+            headers: pairs(&request.headers, config),
+            body: request.read_body().await?.map(RecordedBody::from),
+        })
+    }
+
+    fn matches(&self, other: &RecordedRequest, rules: &MatchRules) -> bool {
+        if rules.method && self.method.parse::<HttpMethod>() != other.method.parse() {
+            return false;
+        }
+
+        if rules.url && self.url != other.url {
+            return false;
+        }
+
+        if rules.headers && normalized(&self.headers) != normalized(&other.headers) {
+            return false;
+        }
+
+        if rules.body
+            && self.body.as_ref().map(RecordedBody::as_bytes)
+                != other.body.as_ref().map(RecordedBody::as_bytes)
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+impl RecordedResponse {
+    fn from_response(response: &Response, config: &CassetteConfig) -> RecordedResponse {
+        RecordedResponse {
+            status_code: response.status_code,
+            version: response.version.clone(),
+            reason: response.reason.clone(),
+            headers: pairs(&response.headers, config),
+            body: RecordedBody::from(response.body.clone()),
+        }
+    }
+
+    fn to_response(&self) -> Response {
+        Response {
+            status_code: self.status_code,
+            version: self.version.clone(),
+            reason: self.reason.clone(),
+            body: self.body.as_bytes().to_vec(),
+            headers: self.headers.iter().cloned().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl RecordedBody {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            RecordedBody::Text(text) => text.as_bytes(),
+            RecordedBody::Binary(bytes) => bytes,
+        }
+    }
+}
+
+impl From<Vec<u8>> for RecordedBody {
+    fn from(bytes: Vec<u8>) -> RecordedBody {
+        match String::from_utf8(bytes) {
+            Ok(text) => RecordedBody::Text(text),
+            Err(err) => RecordedBody::Binary(err.into_bytes()),
+        }
+    }
+}
+
+fn pairs(headers: &Headers, config: &CassetteConfig) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if config.is_redacted(name) {
+                REDACTED
+            } else {
+                value
+            };
+            (name.to_string(), value.to_string())
+        })
+        .collect()
+}
+
+fn normalized(headers: &[(String, String)]) -> Vec<(String, &str)> {
+    let mut headers: Vec<(String, &str)> = headers
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.as_str()))
+        .collect();
+    headers.sort();
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_executor::block_on;
+
+    use super::*;
+
+    fn recorded(
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&str>,
+    ) -> RecordedRequest {
+        RecordedRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.map(|body| RecordedBody::Text(body.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_strict_matching() {
+        let rules = MatchRules::strict();
+        let request = recorded(
+            "POST",
+            "http://api.test/users",
+            &[("Accept", "application/json"), ("X-Trace", "1")],
+            Some("{}"),
+        );
+
+        let reordered = recorded(
+            "POST",
+            "http://api.test/users",
+            &[("x-trace", "1"), ("accept", "application/json")],
+            Some("{}"),
+        );
+        assert!(request.matches(&reordered, &rules));
+
+        let other_header = recorded(
+            "POST",
+            "http://api.test/users",
+            &[("Accept", "application/json"), ("X-Trace", "2")],
+            Some("{}"),
+        );
+        assert!(!request.matches(&other_header, &rules));
+
+        let other_body = recorded(
+            "POST",
+            "http://api.test/users",
+            &[("Accept", "application/json"), ("X-Trace", "1")],
+            Some("[]"),
+        );
+        assert!(!request.matches(&other_body, &rules));
+    }
+
+    #[test]
+    fn test_lenient_matching() {
+        let rules = MatchRules::lenient();
+        let request = recorded("GET", "http://api.test/users?page=1", &[], None);
+
+        let with_extras = recorded(
+            "GET",
+            "http://api.test/users?page=1",
+            &[("X-Trace", "2")],
+            Some("ignored"),
+        );
+        assert!(request.matches(&with_extras, &rules));
+
+        let other_page = recorded("GET", "http://api.test/users?page=2", &[], None);
+        assert!(!request.matches(&other_page, &rules));
+
+        let other_method = recorded("DELETE", "http://api.test/users?page=1", &[], None);
+        assert!(!request.matches(&other_method, &rules));
+    }
+
+    #[test]
+    fn test_redacted_headers() {
+        let mut request = Request::new("http://api.test/users".parse().unwrap());
+        request.set_header("Authorization", "Bearer secret");
+        request.set_header("cookie", "session=secret");
+        request.set_header("X-Api-Key", "secret");
+        request.set_header("Accept", "application/json");
+
+        let mut config = CassetteConfig::default();
+        let recorded = block_on(RecordedRequest::from_request(&request, &config)).unwrap();
+        assert_eq!(
+            normalized(&recorded.headers),
+            [
+                ("accept".to_string(), "application/json"),
+                ("authorization".to_string(), REDACTED),
+                ("cookie".to_string(), REDACTED),
+                ("x-api-key".to_string(), "secret"),
+            ]
+        );
+
+        config.redact_header("x-api-key");
+        let recorded = block_on(RecordedRequest::from_request(&request, &config)).unwrap();
+        assert!(!serde_json::to_string(&recorded).unwrap().contains("secret"));
+
+        config.redacted_headers.clear();
+        let recorded = block_on(RecordedRequest::from_request(&request, &config)).unwrap();
+        assert!(serde_json::to_string(&recorded)
+            .unwrap()
+            .contains("Bearer secret"));
+    }
+
+    #[test]
+    fn test_redacted_response_headers() {
+        let response = Response {
+            status_code: 200,
+            headers: [
+                ("Set-Cookie", "session=secret; HttpOnly"),
+                ("Content-Type", "text/plain"),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+
+        let recorded = RecordedResponse::from_response(&response, &CassetteConfig::default());
+        assert_eq!(
+            recorded.headers,
+            [
+                ("Set-Cookie".to_string(), REDACTED.to_string()),
+                ("Content-Type".to_string(), "text/plain".to_string()),
+            ]
+        );
+        assert!(!serde_json::to_string(&recorded).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn test_body_encoding() {
+        let text = serde_json::to_string(&RecordedBody::from(b"plain".to_vec())).unwrap();
+        assert_eq!(text, r#""plain""#);
+
+        let binary = serde_json::to_string(&RecordedBody::from(vec![0xff, 0x00])).unwrap();
+        assert_eq!(binary, "[255,0]");
+
+        let decoded: RecordedBody = serde_json::from_str(&binary).unwrap();
+        assert_eq!(decoded.as_bytes(), &[0xff, 0x00]);
+    }
+}
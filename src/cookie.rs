@@ -0,0 +1,574 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::Response;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub host_only: bool,
+    pub path: String,
+    #[serde(with = "unix_time")]
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct CookieJar {
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+}
+
+impl Cookie {
+    pub fn new<N: ToString, V: ToString, D: ToString>(name: N, value: V, domain: D) -> Cookie {
+        Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: domain
+                .to_string()
+                .trim_start_matches('.')
+                .to_ascii_lowercase(),
+            host_only: false,
+            path: "/".to_string(),
+            expires: None,
+            secure: false,
+            http_only: false,
+        }
+    }
+
+    pub fn parse(set_cookie: &str, url: &Url) -> Option<Cookie> {
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            expires: None,
+            secure: false,
+            http_only: false,
+        };
+
+        let mut max_age = None;
+
+        for attribute in attributes {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+
+                    // without an interior dot the domain is a top-level one like
+                    // "com", which may only ever name the host itself
+                    if domain != host && !domain.contains('.') {
+                        return None;
+                    }
+
+                    cookie.host_only = domain == host;
+                    cookie.domain = domain;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "expires" => {
+                    if let Ok(expires) = httpdate::parse_http_date(value) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                "max-age" => {
+                    if let Ok(seconds) = value.parse::<i64>() {
+                        max_age = Some(seconds);
+                    }
+                }
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                _ => (),
+            }
+        }
+
+        // Max-Age takes precedence over Expires
+        match max_age {
+            Some(seconds) if seconds <= 0 => cookie.expires = Some(UNIX_EPOCH),
+            Some(seconds) => {
+                let expires = SystemTime::now()
+                    .checked_add(Duration::from_secs(seconds as u64))
+                    .unwrap_or_else(max_expiry);
+                cookie.expires = Some(expires.min(max_expiry()))
+            }
+            None => (),
+        }
+
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .is_some_and(|expires| expires <= SystemTime::now())
+    }
+
+    pub fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+
+        domain_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired()
+    }
+
+    fn is_same(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+impl CookieJar {
+    pub fn new() -> CookieJar {
+        CookieJar::default()
+    }
+
+    pub fn insert(&self, cookie: Cookie) {
+        let mut cookies = self.cookies.lock().unwrap();
+        let existing = cookies
+            .iter()
+            .position(|existing| existing.is_same(&cookie));
+
+        match existing {
+            Some(_) if cookie.is_expired() => {
+                cookies.retain(|existing| !existing.is_same(&cookie));
+            }
+            Some(index) => cookies[index] = cookie,
+            None if cookie.is_expired() => (),
+            None => cookies.push(cookie),
+        }
+    }
+
+    pub fn remove(&self, domain: &str, path: &str, name: &str) -> Option<Cookie> {
+        let mut cookies = self.cookies.lock().unwrap();
+        let index = cookies.iter().position(|cookie| {
+            cookie.domain == domain && cookie.path == path && cookie.name == name
+        })?;
+
+        Some(cookies.remove(index))
+    }
+
+    pub fn clear(&self) {
+        self.cookies.lock().unwrap().clear()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Cookie> {
+        self.cookies()
+            .into_iter()
+            .find(|cookie| cookie.name == name)
+    }
+
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let mut cookies = self.cookies.lock().unwrap();
+        cookies.retain(|cookie| !cookie.is_expired());
+        cookies.clone()
+    }
+
+    pub fn cookies_for(&self, url: &Url) -> Vec<Cookie> {
+        let mut cookies: Vec<Cookie> = self
+            .cookies()
+            .into_iter()
+            .filter(|cookie| cookie.matches(url))
+            .collect();
+
+        // more specific paths go first, the sort is stable so insertion order
+        // is preserved otherwise
+        cookies.sort_by_key(|cookie| Reverse(cookie.path.len()));
+        cookies
+    }
+
+    pub fn header_for(&self, url: &Url) -> Option<String> {
+        let cookies = self.cookies_for(url);
+
+        if cookies.is_empty() {
+            return None;
+        }
+
+        let pairs: Vec<String> = cookies.iter().map(Cookie::to_string).collect();
+        Some(pairs.join("; "))
+    }
+
+    pub fn store(&self, url: &Url, response: &Response) {
+        let heads = response
+            .previous
+            .iter()
+            .map(|head| &head.headers)
+            .chain(Some(&response.headers));
+
+        for headers in heads {
+            for set_cookie in headers.get_all("Set-Cookie") {
+                if let Some(cookie) = Cookie::parse(set_cookie, url) {
+                    self.insert(cookie);
+                }
+            }
+        }
+    }
+
+    pub fn to_netscape(&self) -> String {
+        let mut output = String::from("# Netscape HTTP Cookie File\n");
+
+        for cookie in self.cookies() {
+            let domain = if cookie.host_only {
+                cookie.domain.clone()
+            } else {
+                format!(".{}", cookie.domain)
+            };
+
+            let expires = cookie.expires.map_or(0, unix_seconds);
+
+            output.push_str(&format!(
+                "{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.http_only { "#HttpOnly_" } else { "" },
+                domain,
+                netscape_bool(!cookie.host_only),
+                cookie.path,
+                netscape_bool(cookie.secure),
+                expires,
+                cookie.name,
+                cookie.value,
+            ));
+        }
+
+        output
+    }
+
+    pub fn from_netscape(input: &str) -> io::Result<CookieJar> {
+        let jar = CookieJar::new();
+
+        for (number, line) in input.lines().enumerate() {
+            let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid cookie on line {}", number + 1),
+                )
+            };
+
+            let [domain, include_subdomains, path, secure, expires, name, value] = fields[..]
+            else {
+                return Err(invalid());
+            };
+
+            let expires: u64 = expires.parse().map_err(|_| invalid())?;
+
+            jar.insert(Cookie {
+                name: name.to_string(),
+                value: value.to_string(),
+                domain: domain.trim_start_matches('.').to_ascii_lowercase(),
+                host_only: include_subdomains != "TRUE",
+                path: path.to_string(),
+                expires: (expires > 0).then(|| expiry_at(expires)),
+                secure: secure == "TRUE",
+                http_only,
+            });
+        }
+
+        Ok(jar)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.cookies()).unwrap()
+    }
+
+    pub fn from_json(input: &str) -> io::Result<CookieJar> {
+        let cookies: Vec<Cookie> = serde_json::from_str(input)?;
+
+        let jar = CookieJar::new();
+        for cookie in cookies {
+            jar.insert(cookie);
+        }
+
+        Ok(jar)
+    }
+
+    pub fn save_netscape<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_netscape())
+    }
+
+    pub fn load_netscape<P: AsRef<Path>>(path: P) -> io::Result<CookieJar> {
+        CookieJar::from_netscape(&fs::read_to_string(path)?)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> io::Result<CookieJar> {
+        CookieJar::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.cookies()).finish()
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+
+    // IP addresses only match exactly
+    host.parse::<IpAddr>().is_err()
+        && host.ends_with(domain)
+        && host[..host.len() - domain.len()].ends_with('.')
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => url.path()[..index].to_string(),
+    }
+}
+
+fn netscape_bool(value: bool) -> &'static str {
+    if value {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+// 9999-12-31T23:59:59Z, the latest date an Expires attribute can carry
+const MAX_EXPIRY: u64 = 253_402_300_799;
+
+fn max_expiry() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(MAX_EXPIRY)
+}
+
+fn expiry_at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.min(MAX_EXPIRY))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+mod unix_time {
+    use std::time::SystemTime;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, s: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => s.serialize_some(&super::unix_seconds(*time)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<SystemTime>, D::Error> {
+        let seconds: Option<u64> = Option::deserialize(d)?;
+        Ok(seconds.map(super::expiry_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cookie = Cookie::parse(
+            "sid=abc; Domain=.Example.com; Path=/app; Secure; HttpOnly; Max-Age=60",
+            &url("https://api.example.com/login"),
+        )
+        .unwrap();
+
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/app");
+        assert!(cookie.secure && cookie.http_only);
+        assert!(cookie.expires.unwrap() > SystemTime::now());
+
+        let cookie = Cookie::parse("lang=en", &url("http://example.com/a/b/c")).unwrap();
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/a/b");
+        assert_eq!(cookie.expires, None);
+
+        assert!(Cookie::parse("x=1; Domain=other.com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("x=1; Domain=le.com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("no_value", &url("http://example.com/")).is_none());
+
+        let origin = url("http://api.example.com/");
+        assert!(Cookie::parse("x=1; Domain=com", &origin).is_none());
+        assert!(Cookie::parse("x=1; Domain=.com", &origin).is_none());
+        assert!(Cookie::parse("x=1; Domain=example.com", &origin).is_some());
+
+        let cookie = Cookie::parse("x=1; Domain=localhost", &url("http://localhost/")).unwrap();
+        assert!(cookie.host_only);
+    }
+
+    #[test]
+    fn test_expiry_overflow() {
+        let origin = url("http://example.com/");
+
+        let cookie = Cookie::parse("a=1; Max-Age=9223372036854775807", &origin).unwrap();
+        assert_eq!(cookie.expires, Some(max_expiry()));
+        assert!(!cookie.is_expired());
+
+        let netscape = "example.com\tFALSE\t/\tFALSE\t18446744073709551615\tb\t2\n";
+        let jar = CookieJar::from_netscape(netscape).unwrap();
+        assert_eq!(jar.get("b").unwrap().expires, Some(max_expiry()));
+
+        let jar = CookieJar::from_json(r#"[{"name":"c","value":"3","domain":"example.com","host_only":true,"path":"/","expires":18446744073709551615,"secure":false,"http_only":false}]"#).unwrap();
+        assert_eq!(jar.get("c").unwrap().expires, Some(max_expiry()));
+    }
+
+    #[test]
+    fn test_matching_rules() {
+        let jar = CookieJar::new();
+        let origin = url("http://www.example.com/app/login");
+
+        jar.store(
+            &origin,
+            &Response {
+                headers: [
+                    ("Set-Cookie", "host=1"),
+                    ("Set-Cookie", "wide=2; Domain=example.com; Path=/"),
+                    ("Set-Cookie", "app=3; Path=/app"),
+                    ("Set-Cookie", "secure=4; Path=/; Secure"),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            jar.header_for(&url("http://www.example.com/app/users")),
+            Some("host=1; app=3; wide=2".to_string())
+        );
+        assert_eq!(
+            jar.header_for(&url("https://www.example.com/")),
+            Some("wide=2; secure=4".to_string())
+        );
+        assert_eq!(
+            jar.header_for(&url("https://static.example.com/")),
+            Some("wide=2".to_string())
+        );
+        assert_eq!(
+            jar.header_for(&url("http://www.example.com/application")),
+            Some("wide=2".to_string())
+        );
+        assert_eq!(jar.header_for(&url("http://example.org/")), None);
+    }
+
+    #[test]
+    fn test_replace_and_expire() {
+        let jar = CookieJar::new();
+        let origin = url("http://example.com/");
+
+        jar.insert(Cookie::parse("a=1", &origin).unwrap());
+        jar.insert(Cookie::parse("a=2", &origin).unwrap());
+        assert_eq!(jar.cookies().len(), 1);
+        assert_eq!(jar.get("a").unwrap().value, "2");
+
+        jar.insert(Cookie::parse("a=gone; Max-Age=0", &origin).unwrap());
+        assert!(jar.cookies().is_empty());
+
+        jar.insert(Cookie::parse("b=old; Expires=Wed, 21 Oct 2015 07:28:00 GMT", &origin).unwrap());
+        assert!(jar.get("b").is_none());
+    }
+
+    #[test]
+    fn test_netscape_roundtrip() {
+        let jar = CookieJar::new();
+        let mut cookie = Cookie::new("sid", "abc", ".example.com");
+        cookie.secure = true;
+        cookie.http_only = true;
+        cookie.expires = Some(UNIX_EPOCH + Duration::from_secs(4_102_444_800));
+        jar.insert(cookie.clone());
+
+        let mut session = Cookie::new("lang", "en", "www.example.com");
+        session.host_only = true;
+        session.path = "/app".to_string();
+        jar.insert(session.clone());
+
+        let netscape = jar.to_netscape();
+        assert!(netscape.contains("#HttpOnly_.example.com\tTRUE\t/\tTRUE\t4102444800\tsid\tabc\n"));
+        assert!(netscape.contains("www.example.com\tFALSE\t/app\tFALSE\t0\tlang\ten\n"));
+
+        let loaded = CookieJar::from_netscape(&netscape).unwrap();
+        assert_eq!(loaded.cookies(), vec![cookie, session]);
+
+        assert!(CookieJar::from_netscape("example.com\tTRUE\t/").is_err());
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let jar = CookieJar::new();
+        let mut cookie = Cookie::new("sid", "abc", "example.com");
+        cookie.expires = Some(UNIX_EPOCH + Duration::from_secs(4_102_444_800));
+        jar.insert(cookie);
+        jar.insert(Cookie::new("lang", "en", "example.com"));
+
+        let loaded = CookieJar::from_json(&jar.to_json()).unwrap();
+        assert_eq!(loaded.cookies(), jar.cookies());
+    }
+}
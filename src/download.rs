@@ -0,0 +1,466 @@
+use std::fs::{self, File};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ::curl::easy::Easy;
+use log::trace;
+
+use crate::driver::Resume;
+use crate::middleware::{rewind, Attempt, Endpoint};
+use crate::{
+    random, transfer_error, BoxFuture, Error, ErrorKind, HttpClient, Interceptor, Progress,
+    Request, Response, Timeouts,
+};
+
+type ProgressFn = dyn FnMut(Progress) + Send;
+
+struct Target {
+    // taken once the download returns, while an abandoned transfer may still hold the target
+    file: Option<File>,
+    written: u64,
+    total: Option<u64>,
+    resumable: bool,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    headers: Vec<String>,
+    mode: Option<Mode>,
+    // set while the request carries range headers the download added itself
+    ranged: bool,
+    error_body: Vec<u8>,
+    failed: Option<io::Error>,
+    progress: Box<ProgressFn>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Write,
+    Discard,
+    Changed,
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub async fn download<P: AsRef<Path>>(
+        &self,
+        request: Request,
+        path: P,
+    ) -> Result<Response, Error> {
+        self.download_with_progress(request, path, |_| ()).await
+    }
+
+    pub async fn download_with_progress<P, F>(
+        &self,
+        mut request: Request,
+        path: P,
+        progress: F,
+    ) -> Result<Response, Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Progress) + Send + 'static,
+    {
+        let path = path.as_ref();
+
+        let temp_path = match temp_path(path) {
+            Ok(temp_path) => temp_path,
+            Err(err) => return Err((request, err).into()),
+        };
+
+        let file = match File::options()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => file,
+            Err(err) => return Err((request, err).into()),
+        };
+
+        let target = Arc::new(Mutex::new(Target {
+            file: Some(file),
+            written: 0,
+            total: None,
+            resumable: false,
+            etag: None,
+            last_modified: None,
+            headers: vec![],
+            mode: None,
+            ranged: false,
+            error_body: vec![],
+            failed: None,
+            progress: Box::new(progress),
+        }));
+
+        let endpoint = Download {
+            client: self,
+            target: &target,
+        };
+
+        let outcome = self
+            .dispatch_to(&mut request, &endpoint)
+            .await
+            .and_then(|mut response| {
+                let mut target = target.lock().unwrap();
+
+                // custom transports, and middleware answering on its own, leave the body
+                // in the response instead of writing it to the file
+                if target.mode.is_none() && (200..300).contains(&response.status_code) {
+                    let body = std::mem::take(&mut response.body);
+                    target.fill(&body).map_err(ErrorKind::Io)?;
+                }
+
+                Ok(response)
+            });
+
+        let file = target.lock().unwrap().file.take().unwrap();
+
+        let result = match outcome {
+            Ok(response) if (200..300).contains(&response.status_code) => {
+                match file.sync_all().and_then(|_| fs::rename(&temp_path, path)) {
+                    Ok(()) => Ok(response),
+                    Err(err) => Err((request, err).into()),
+                }
+            }
+            Ok(response) => Err((request, response).into()),
+            Err(kind) => Err(Error {
+                request: Box::new(request),
+                kind,
+            }),
+        };
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+
+        result
+    }
+}
+
+struct Download<'c, X: Interceptor> {
+    client: &'c HttpClient<X>,
+    target: &'c Arc<Mutex<Target>>,
+}
+
+impl<X: Interceptor> Endpoint for Download<'_, X> {
+    fn send<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            if self.client.transport.is_some() {
+                return Endpoint::send(self.client, request).await;
+            }
+
+            rewind(request)?;
+
+            if let Some(form) = &request.form {
+                form.validate().map_err(ErrorKind::InvalidHeader)?;
+            }
+
+            let mut downloading = Downloading {
+                client: self.client,
+                target: self.target,
+                resume: Arc::default(),
+                prepared: None,
+            };
+
+            // attempts add range headers, so they work on a copy
+            self.client
+                .send_hops(&mut request.share(), &mut downloading)
+                .await
+        })
+    }
+}
+
+struct Downloading<'c, X: Interceptor> {
+    client: &'c HttpClient<X>,
+    target: &'c Arc<Mutex<Target>>,
+    resume: Arc<Resume>,
+    prepared: Option<(Easy, Timeouts)>,
+}
+
+impl<X: Interceptor> Downloading<'_, X> {
+    fn prepare_easy(&self, request: &mut Request) -> Result<(Easy, Timeouts), ErrorKind> {
+        self.target.lock().unwrap().prepare_attempt(request);
+
+        let (mut easy, timeouts) = self.client.prepare_easy(request, &self.resume)?;
+        write_to_target(&mut easy, self.target);
+
+        Ok((easy, timeouts))
+    }
+}
+
+impl<X: Interceptor> Attempt for Downloading<'_, X> {
+    fn prepare(&mut self, request: &mut Request) -> Result<(), ErrorKind> {
+        self.prepared = Some(self.prepare_easy(request)?);
+        Ok(())
+    }
+
+    fn perform<'a>(
+        &'a mut self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            loop {
+                let (easy, timeouts) = match self.prepared.take() {
+                    Some(prepared) => prepared,
+                    None => self.prepare_easy(request)?,
+                };
+
+                let (easy, result) = self
+                    .client
+                    .driver
+                    .perform(easy, self.resume.clone(), self.client.reservation.take())
+                    .await;
+
+                let mut target = self.target.lock().unwrap();
+
+                if let Some(err) = target.failed.take() {
+                    return Err(ErrorKind::Io(err));
+                }
+
+                if result.is_ok() && target.mode.is_none() {
+                    target.begin();
+                }
+
+                if target.mode == Some(Mode::Changed) {
+                    trace!(
+                        "{:?} changed between attempts, restarting download",
+                        request.url.as_str()
+                    );
+                    continue;
+                }
+
+                return match result {
+                    Ok(()) => {
+                        let body = std::mem::take(&mut target.error_body);
+                        Ok(Response::from_raw(
+                            easy.response_code().unwrap(),
+                            &target.headers,
+                            body,
+                        ))
+                    }
+                    Err(err) => Err(transfer_error(err, &easy, &timeouts)),
+                };
+            }
+        })
+    }
+
+    fn pause(&self, delay: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(self.client.driver.delay(delay))
+    }
+}
+
+impl Target {
+    fn prepare_attempt(&mut self, request: &mut Request) {
+        self.headers.clear();
+        self.error_body.clear();
+
+        if self.mode == Some(Mode::Changed) {
+            self.written = 0;
+            self.resumable = false;
+        }
+        self.mode = None;
+
+        if self.ranged {
+            request.remove_header("Range");
+            request.remove_header("If-Range");
+            self.ranged = false;
+        }
+
+        // a range the caller asked for is left alone, and the download isn't resumed
+        let caller_range =
+            request.headers.contains("Range") || request.headers.contains("If-Range");
+
+        if self.resumable && self.written > 0 && !caller_range {
+            request.set_header("Range", format!("bytes={}-", self.written));
+
+            if let Some(validator) = self.etag.as_ref().or(self.last_modified.as_ref()) {
+                request.set_header("If-Range", validator);
+            }
+
+            self.ranged = true;
+        }
+    }
+
+    fn fill(&mut self, body: &[u8]) -> io::Result<()> {
+        self.restart()?;
+        self.total = Some(body.len() as u64);
+        self.mode = Some(Mode::Write);
+        self.write(body);
+
+        match self.failed.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn begin(&mut self) {
+        let head = Response::from_head(&self.headers);
+
+        let mode = match head.status_code {
+            206 if self.ranged => {
+                let range = head.header("Content-Range").and_then(parse_content_range);
+                let unchanged = range.is_some_and(|(start, _)| start == self.written)
+                    && self.validators_match(&head);
+
+                if unchanged {
+                    self.total = range.and_then(|(_, total)| total);
+                    Mode::Write
+                } else {
+                    Mode::Changed
+                }
+            }
+            200..=299 => {
+                if let Err(err) = self.restart() {
+                    self.failed = Some(err);
+                }
+
+                self.total = head
+                    .header("Content-Length")
+                    .and_then(|length| length.parse().ok());
+                self.resumable = head
+                    .header("Accept-Ranges")
+                    .is_some_and(|units| units.split(',').any(|unit| unit.trim() == "bytes"));
+                self.etag = head.header("ETag").map(str::to_string);
+                self.last_modified = head.header("Last-Modified").map(str::to_string);
+
+                Mode::Write
+            }
+            _ => Mode::Discard,
+        };
+
+        self.mode = Some(mode);
+    }
+
+    fn validators_match(&self, head: &Response) -> bool {
+        let etag_matches = self
+            .etag
+            .as_deref()
+            .is_none_or(|etag| head.header("ETag") == Some(etag));
+        let last_modified_matches = self
+            .last_modified
+            .as_deref()
+            .is_none_or(|date| head.header("Last-Modified") == Some(date));
+
+        etag_matches && last_modified_matches
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        self.file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("the download was abandoned"))
+    }
+
+    fn restart(&mut self) -> io::Result<()> {
+        self.written = 0;
+        let file = self.file()?;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+
+    fn write(&mut self, chunk: &[u8]) -> bool {
+        if self.mode.is_none() {
+            self.begin();
+        }
+
+        if self.failed.is_some() {
+            return false;
+        }
+
+        match self.mode {
+            Some(Mode::Write) => {
+                if let Err(err) = self.file().and_then(|file| file.write_all(chunk)) {
+                    self.failed = Some(err);
+                    return false;
+                }
+
+                self.written += chunk.len() as u64;
+                // counts what's in the file, so a resumed download doesn't start over at zero
+                (self.progress)(Progress {
+                    downloaded: self.written,
+                    download_total: self.total,
+                    ..Default::default()
+                });
+
+                true
+            }
+            Some(Mode::Discard) => {
+                self.error_body.extend_from_slice(chunk);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn write_to_target(easy: &mut Easy, target: &Arc<Mutex<Target>>) {
+    let body = target.clone();
+    easy.write_function(move |chunk| {
+        if body.lock().unwrap().write(chunk) {
+            Ok(chunk.len())
+        } else {
+            Ok(0)
+        }
+    })
+    .unwrap();
+
+    let headers = target.clone();
+    easy.header_function(move |header| {
+        headers
+            .lock()
+            .unwrap()
+            .headers
+            .push(Response::head_line(header));
+        true
+    })
+    .unwrap();
+}
+
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        ));
+    };
+
+    // concurrent downloads to the same destination each get their own
+    let mut temp_name = file_name.to_os_string();
+    temp_name.push(format!(".{:016x}.part", random()));
+
+    Ok(path.with_file_name(temp_name))
+}
+
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 50-99/100"),
+            Some((50, Some(100)))
+        );
+        assert_eq!(parse_content_range("bytes 50-99/*"), Some((50, None)));
+        assert_eq!(parse_content_range("bytes */100"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn test_temp_path() {
+        let path = Path::new("/tmp/artifact.tar.gz");
+        let temp = temp_path(path).unwrap();
+        let name = temp.file_name().unwrap().to_str().unwrap();
+
+        assert_eq!(temp.parent(), Some(Path::new("/tmp")));
+        assert!(name.starts_with("artifact.tar.gz.") && name.ends_with(".part"));
+        assert_ne!(temp, temp_path(path).unwrap());
+        assert!(temp_path(Path::new("/")).is_err());
+    }
+}
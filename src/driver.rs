@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use curl::easy::Easy;
+use curl::multi::{EasyHandle, Multi, MultiWaker};
+use curl::MultiError;
+use futures_channel::oneshot;
+
+use log::trace;
+
+use crate::body::Pump;
+
+// new transfers, resumed transfers and dropped requests wake the driver up, so this
+// only bounds how long it sleeps when nothing does
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// CURLE_FAILED_INIT, reported for failures of the multi handle itself
+const FAILED_INIT: u32 = 2;
+
+pub(crate) type Completion = (Easy, Result<(), curl::Error>);
+
+#[derive(Default)]
+pub(crate) struct Resume {
+    requested: AtomicBool,
+    // feeds an async request body from the task awaiting the transfer
+    pump: Mutex<Option<Arc<Pump>>>,
+    wakeup: OnceLock<Wakeup>,
+}
+
+impl Resume {
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.wake_driver();
+    }
+
+    pub fn set_pump(&self, pump: Arc<Pump>) {
+        *self.pump.lock().unwrap() = Some(pump)
+    }
+
+    fn take(&self) -> bool {
+        self.requested.swap(false, Ordering::SeqCst)
+    }
+
+    fn wake_driver(&self) {
+        if let Some(wakeup) = self.wakeup.get() {
+            wakeup.wake();
+        }
+    }
+
+    fn pump(&self, cx: &mut Context<'_>) {
+        let pump = self.pump.lock().unwrap().clone();
+
+        if let Some(pump) = pump {
+            pump.poll(cx, self);
+        }
+    }
+}
+
+impl Wake for Resume {
+    fn wake(self: Arc<Self>) {
+        self.request()
+    }
+}
+
+pub(crate) struct Transfer {
+    rx: oneshot::Receiver<Completion>,
+    resume: Arc<Resume>,
+    slot: Option<Slot>,
+}
+
+impl Future for Transfer {
+    type Output = Completion;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Completion> {
+        self.resume.pump(cx);
+
+        let completion = match Pin::new(&mut self.rx).poll(cx) {
+            Poll::Ready(completion) => completion.expect("http driver thread terminated"),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        self.slot = None;
+        Poll::Ready(completion)
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        // an unfinished transfer is aborted by the driver once it notices, so the
+        // receiver is closed before the driver is woken to look
+        if self.slot.is_some() {
+            self.rx.close();
+            self.resume.wake_driver();
+        }
+    }
+}
+
+// wakes the driver thread out of Multi::poll. Set once the thread has its multi handle,
+// and anything sent before that is picked up before it first polls.
+#[derive(Clone, Default)]
+struct Wakeup(Arc<OnceLock<MultiWaker>>);
+
+impl Wakeup {
+    fn wake(&self) {
+        if let Some(waker) = self.0.get() {
+            // fails only once the driver thread is gone
+            let _ = waker.wakeup();
+        }
+    }
+}
+
+#[derive(Default)]
+struct Capacity {
+    limit: AtomicUsize,
+    in_flight: AtomicUsize,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl Capacity {
+    #[cfg(feature = "tower")]
+    fn try_reserve(self: &Arc<Self>) -> Option<Slot> {
+        let limit = self.limit.load(Ordering::SeqCst);
+
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |in_flight| {
+                (limit == 0 || in_flight < limit).then_some(in_flight + 1)
+            })
+            .ok()
+            .map(|_| Slot(self.clone()))
+    }
+
+    fn wake_waiters(&self) {
+        for waker in self.waiters.lock().unwrap().drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+pub(crate) struct Slot(Arc<Capacity>);
+
+impl Slot {
+    fn acquire(capacity: &Arc<Capacity>) -> Slot {
+        capacity.in_flight.fetch_add(1, Ordering::SeqCst);
+        Slot(capacity.clone())
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.0.wake_waiters();
+    }
+}
+
+// a slot taken by Service::poll_ready, waiting for the call's first transfer.
+// Clones start out empty, so a reservation is only ever used once.
+#[derive(Default)]
+pub(crate) struct Reservation(Mutex<Option<Slot>>);
+
+impl Reservation {
+    #[cfg(feature = "tower")]
+    pub fn is_held(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    #[cfg(feature = "tower")]
+    pub fn hold(&self, slot: Slot) {
+        *self.0.lock().unwrap() = Some(slot)
+    }
+
+    pub fn take(&self) -> Option<Slot> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl Clone for Reservation {
+    fn clone(&self) -> Reservation {
+        Reservation::default()
+    }
+}
+
+enum Message {
+    Perform(Easy, Arc<Resume>, oneshot::Sender<Completion>),
+    Delay(Instant, oneshot::Sender<()>),
+    MaxConnections(usize),
+}
+
+// the thread is spawned on first use, so clients that never perform a
+// transfer through the driver (blocking or custom transports) don't pay for it
+pub(crate) struct Driver {
+    tx: Mutex<Option<Sender<Message>>>,
+    capacity: Arc<Capacity>,
+    wakeup: Wakeup,
+}
+
+impl Driver {
+    pub fn new() -> Driver {
+        Driver {
+            tx: Mutex::new(None),
+            capacity: Arc::default(),
+            wakeup: Wakeup::default(),
+        }
+    }
+
+    pub fn perform(&self, easy: Easy, resume: Arc<Resume>, reserved: Option<Slot>) -> Transfer {
+        let (tx, rx) = oneshot::channel();
+        let slot = reserved.unwrap_or_else(|| Slot::acquire(&self.capacity));
+        let _ = resume.wakeup.set(self.wakeup.clone());
+        self.send(Message::Perform(easy, resume.clone(), tx));
+
+        Transfer {
+            rx,
+            resume,
+            slot: Some(slot),
+        }
+    }
+
+    pub fn set_max_connections(&self, max: usize) {
+        self.capacity.limit.store(max, Ordering::SeqCst);
+        self.capacity.wake_waiters();
+        self.send(Message::MaxConnections(max));
+    }
+
+    #[cfg(feature = "tower")]
+    pub fn poll_reserve(&self, cx: &mut Context<'_>) -> Poll<Slot> {
+        if let Some(slot) = self.capacity.try_reserve() {
+            return Poll::Ready(slot);
+        }
+
+        self.capacity
+            .waiters
+            .lock()
+            .unwrap()
+            .push(cx.waker().clone());
+
+        // a transfer may have finished while the waker was being registered
+        match self.capacity.try_reserve() {
+            Some(slot) => Poll::Ready(slot),
+            None => Poll::Pending,
+        }
+    }
+
+    pub fn delay(&self, duration: Duration) -> impl Future<Output = ()> {
+        let (tx, rx) = oneshot::channel();
+        self.send(Message::Delay(Instant::now() + duration, tx));
+
+        async move { rx.await.expect("http driver thread terminated") }
+    }
+
+    fn send(&self, message: Message) {
+        let mut tx = self.tx.lock().unwrap();
+
+        let tx = tx.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel();
+            let wakeup = self.wakeup.clone();
+
+            thread::Builder::new()
+                .name("chipp_http".to_string())
+                .spawn(move || run(rx, wakeup))
+                .expect("failed to spawn http driver thread");
+
+            tx
+        });
+
+        tx.send(message).expect("http driver thread terminated");
+        self.wakeup.wake();
+    }
+}
+
+struct Active {
+    handle: EasyHandle,
+    resume: Arc<Resume>,
+    tx: oneshot::Sender<Completion>,
+}
+
+fn run(rx: Receiver<Message>, wakeup: Wakeup) {
+    let mut multi = Multi::new();
+    let _ = wakeup.0.set(multi.waker());
+
+    let mut active: HashMap<usize, Active> = HashMap::new();
+    let mut delays: Vec<(Instant, oneshot::Sender<()>)> = vec![];
+    let mut next_token = 0;
+    let mut disconnected = false;
+
+    loop {
+        let mut messages = vec![];
+
+        if active.is_empty() {
+            if disconnected && delays.is_empty() {
+                break;
+            }
+
+            let next_deadline = delays.iter().map(|(deadline, _)| *deadline).min();
+            let received = match next_deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(message) => messages.push(message),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => disconnected = true,
+            }
+        }
+
+        loop {
+            match rx.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+
+        for message in messages {
+            match message {
+                Message::Perform(easy, resume, tx) => {
+                    let mut handle = match multi.add(easy) {
+                        Ok(handle) => handle,
+                        Err(err) => {
+                            let _ = tx.send((Easy::new(), Err(driver_error(err))));
+                            continue;
+                        }
+                    };
+
+                    if let Err(err) = handle.set_token(next_token) {
+                        let easy = multi.remove(handle).unwrap_or_else(|_| Easy::new());
+                        let _ = tx.send((easy, Err(err)));
+                        continue;
+                    }
+
+                    active.insert(next_token, Active { handle, resume, tx });
+                    next_token = next_token.wrapping_add(1);
+                }
+                Message::Delay(deadline, tx) => delays.push((deadline, tx)),
+                Message::MaxConnections(max) => {
+                    if let Err(err) = multi.set_max_total_connections(max) {
+                        trace!("failed to limit connections to {}: {}", max, err);
+                    }
+                }
+            }
+        }
+
+        let canceled: Vec<usize> = active
+            .iter()
+            .filter(|(_, transfer)| transfer.tx.is_canceled())
+            .map(|(token, _)| *token)
+            .collect();
+
+        for token in canceled {
+            let transfer = active.remove(&token).unwrap();
+            let _ = multi.remove(transfer.handle);
+
+            trace!("transfer {} aborted because its request was dropped", token);
+        }
+
+        delays.retain(|(_, tx)| !tx.is_canceled());
+
+        let now = Instant::now();
+        let (expired, pending) = delays
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        delays = pending;
+
+        for (_, tx) in expired {
+            let _ = tx.send(());
+        }
+
+        if active.is_empty() {
+            continue;
+        }
+
+        for transfer in active.values() {
+            if transfer.resume.take() {
+                // a failed unpause leaves the transfer to its timeouts
+                let _ = transfer.handle.unpause_read();
+                let _ = transfer.handle.unpause_write();
+            }
+        }
+
+        if let Err(err) = multi.perform() {
+            fail_all(&multi, &mut active, err);
+            continue;
+        }
+
+        let mut finished = vec![];
+        multi.messages(|message| {
+            if let Ok(token) = message.token() {
+                if let Some(transfer) = active.get(&token) {
+                    if let Some(result) = message.result_for(&transfer.handle) {
+                        finished.push((token, result));
+                    }
+                }
+            }
+        });
+
+        if finished.iter().any(|(_, result)| result.is_err()) {
+            clear_callback_panic();
+        }
+
+        for (token, result) in finished {
+            let transfer = active.remove(&token).unwrap();
+            let easy = match multi.remove(transfer.handle) {
+                Ok(easy) => easy,
+                Err(err) => {
+                    let _ = transfer.tx.send((Easy::new(), Err(driver_error(err))));
+                    continue;
+                }
+            };
+
+            if transfer.tx.send((easy, result)).is_err() {
+                trace!("transfer {} finished after its request was dropped", token);
+            }
+        }
+
+        if !active.is_empty() {
+            let timeout = delays
+                .iter()
+                .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()))
+                .fold(POLL_INTERVAL, Duration::min);
+
+            if let Err(err) = multi.poll(&mut [], timeout) {
+                fail_all(&multi, &mut active, err);
+            }
+        }
+    }
+}
+
+fn fail(multi: &Multi, transfer: Active, err: MultiError) {
+    let easy = multi
+        .remove(transfer.handle)
+        .unwrap_or_else(|_| Easy::new());
+    let _ = transfer.tx.send((easy, Err(driver_error(err))));
+}
+
+fn fail_all(multi: &Multi, active: &mut HashMap<usize, Active>, err: MultiError) {
+    trace!("failing {} transfers: {}", active.len(), err);
+
+    for (_, transfer) in active.drain() {
+        fail(multi, transfer, err.clone());
+    }
+}
+
+fn driver_error(err: MultiError) -> curl::Error {
+    let mut error = curl::Error::new(FAILED_INIT);
+    error.set_extra(err.to_string());
+    error
+}
+
+// curl-rust keeps a panic raised in one of our callbacks in a thread local and
+// skips every later callback on this thread until it's re-raised, which only
+// Easy::perform does. The panicking transfer has already failed, so drop it.
+fn clear_callback_panic() {
+    let _ = panic::catch_unwind(|| Easy::new().perform());
+}
@@ -1,21 +1,35 @@
-use crate::{Request, Response};
+use crate::{HttpMethod, Request, Response};
 use std::error::Error as StdError;
 
 pub struct Error {
-    pub request: Request,
+    pub request: Box<Request>,
     pub kind: ErrorKind,
 }
 
 pub enum ErrorKind {
-    HttpError(Response),
+    HttpError(Box<Response>),
     CurlError(curl::Error),
     JsonParseError(serde_json::Error),
+    Timeout { phase: TimeoutPhase },
+    InvalidHeader(String),
+    Io(std::io::Error),
+    TooManyRedirects(Box<Response>),
+    RedirectBodyNotRewindable(Box<Response>),
+    BodyNotAllowed(HttpMethod),
+    InvalidMethod(HttpMethod),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeoutPhase {
+    Connect,
+    Total,
+    LowSpeed,
 }
 
 impl From<(Request, curl::Error)> for Error {
     fn from(pair: (Request, curl::Error)) -> Error {
         Error {
-            request: pair.0,
+            request: Box::new(pair.0),
             kind: ErrorKind::CurlError(pair.1),
         }
     }
@@ -24,17 +38,35 @@ impl From<(Request, curl::Error)> for Error {
 impl From<(Request, serde_json::Error)> for Error {
     fn from(pair: (Request, serde_json::Error)) -> Error {
         Error {
-            request: pair.0,
+            request: Box::new(pair.0),
             kind: ErrorKind::JsonParseError(pair.1),
         }
     }
 }
 
+impl From<(Request, TimeoutPhase)> for Error {
+    fn from(pair: (Request, TimeoutPhase)) -> Error {
+        Error {
+            request: Box::new(pair.0),
+            kind: ErrorKind::Timeout { phase: pair.1 },
+        }
+    }
+}
+
+impl From<(Request, std::io::Error)> for Error {
+    fn from(pair: (Request, std::io::Error)) -> Error {
+        Error {
+            request: Box::new(pair.0),
+            kind: ErrorKind::Io(pair.1),
+        }
+    }
+}
+
 impl From<(Request, Response)> for Error {
     fn from(pair: (Request, Response)) -> Error {
         Error {
-            request: pair.0,
-            kind: ErrorKind::HttpError(pair.1),
+            request: Box::new(pair.0),
+            kind: ErrorKind::HttpError(Box::new(pair.1)),
         }
     }
 }
@@ -63,6 +95,41 @@ impl fmt::Debug for Error {
                 .field("request", &self.request)
                 .field("response", &response)
                 .finish(),
+            Timeout { phase } => f
+                .debug_struct("Timeout")
+                .field("request", &self.request)
+                .field("phase", &phase)
+                .finish(),
+            InvalidHeader(name) => f
+                .debug_struct("InvalidHeader")
+                .field("request", &self.request)
+                .field("name", &name)
+                .finish(),
+            Io(err) => f
+                .debug_struct("Io")
+                .field("request", &self.request)
+                .field("error", &err)
+                .finish(),
+            TooManyRedirects(response) => f
+                .debug_struct("TooManyRedirects")
+                .field("request", &self.request)
+                .field("response", &response)
+                .finish(),
+            RedirectBodyNotRewindable(response) => f
+                .debug_struct("RedirectBodyNotRewindable")
+                .field("request", &self.request)
+                .field("response", &response)
+                .finish(),
+            BodyNotAllowed(method) => f
+                .debug_struct("BodyNotAllowed")
+                .field("request", &self.request)
+                .field("method", &method)
+                .finish(),
+            InvalidMethod(method) => f
+                .debug_struct("InvalidMethod")
+                .field("request", &self.request)
+                .field("method", &method)
+                .finish(),
         }
     }
 }
@@ -72,9 +139,32 @@ impl fmt::Display for Error {
         use ErrorKind::*;
 
         match &self.kind {
-            CurlError(err) => curl::Error::fmt(&err, f),
-            JsonParseError(err) => serde_json::Error::fmt(&err, f),
+            CurlError(err) => curl::Error::fmt(err, f),
+            JsonParseError(err) => serde_json::Error::fmt(err, f),
             HttpError(res) => write!(f, "HTTP Error: {}", res),
+            Timeout { phase } => write!(f, "Timeout: {}", phase),
+            InvalidHeader(name) => write!(f, "Invalid header: {:?}", name),
+            Io(err) => std::io::Error::fmt(err, f),
+            TooManyRedirects(res) => {
+                write!(f, "Too many redirects after {} hop(s)", res.redirects.len())
+            }
+            RedirectBodyNotRewindable(res) => write!(
+                f,
+                "Can't resend the request body to follow a {} redirect",
+                res.status_code
+            ),
+            BodyNotAllowed(method) => write!(f, "{} requests can't have a body", method),
+            InvalidMethod(method) => write!(f, "Invalid method: {:?}", method.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutPhase::Connect => write!(f, "connection was not established in time"),
+            TimeoutPhase::Total => write!(f, "request did not complete in time"),
+            TimeoutPhase::LowSpeed => write!(f, "transfer was too slow"),
         }
     }
 }
@@ -100,3 +190,14 @@ impl fmt::Display for UrlParseError {
 }
 
 impl StdError for UrlParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidMethod(pub String);
+
+impl fmt::Display for InvalidMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid method: {:?}", self.0)
+    }
+}
+
+impl StdError for InvalidMethod {}
@@ -0,0 +1,224 @@
+use std::fmt;
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+    unset: Vec<String>,
+}
+
+impl Headers {
+    pub fn new() -> Headers {
+        Headers::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn insert<K: ToString, V: ToString>(&mut self, name: K, value: V) {
+        let name = name.to_string();
+        self.remove(&name);
+        self.entries.push((name, value.to_string()))
+    }
+
+    pub fn append<K: ToString, V: ToString>(&mut self, name: K, value: V) {
+        let name = name.to_string();
+        self.unset
+            .retain(|unset| !unset.eq_ignore_ascii_case(&name));
+        self.entries.push((name, value.to_string()))
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        self.unset.retain(|unset| !unset.eq_ignore_ascii_case(name));
+    }
+
+    pub fn unset<K: ToString>(&mut self, name: K) {
+        let name = name.to_string();
+        self.remove(&name);
+        self.unset.push(name)
+    }
+
+    pub fn is_unset(&self, name: &str) -> bool {
+        self.unset
+            .iter()
+            .any(|unset| unset.eq_ignore_ascii_case(name))
+    }
+
+    pub fn unset_names(&self) -> impl Iterator<Item = &str> {
+        self.unset.iter().map(String::as_str)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let names = self.entries.iter().map(|(name, _)| name).chain(&self.unset);
+
+        for name in names {
+            if name.is_empty() || !name.bytes().all(is_token) {
+                return Err(name.clone());
+            }
+        }
+
+        for (name, value) in &self.entries {
+            if value.bytes().any(|b| b == b'\r' || b == b'\n' || b == 0) {
+                return Err(name.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn overridden_by(&self, overrides: &Headers) -> Headers {
+        let mut headers = self.clone();
+
+        for (name, _) in &overrides.entries {
+            headers.remove(name);
+        }
+
+        for name in &overrides.unset {
+            headers.remove(name);
+        }
+
+        headers.entries.extend(overrides.entries.iter().cloned());
+        headers.unset.extend(overrides.unset.iter().cloned());
+
+        headers
+    }
+
+    pub(crate) fn extend_last(&mut self, continuation: &str) {
+        if let Some((_, value)) = self.entries.last_mut() {
+            value.push(' ');
+            value.push_str(continuation);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+pub(crate) fn is_token(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+impl fmt::Debug for Headers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: ToString, V: ToString> FromIterator<(K, V)> for Headers {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Headers {
+        let mut headers = Headers::new();
+        for (name, value) in iter {
+            headers.append(name, value);
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut headers = Headers::new();
+        headers.append("Accept", "text/plain");
+        headers.append("accept", "text/html");
+        headers.insert("ACCEPT", "application/json");
+
+        assert_eq!(
+            headers.get_all("Accept").collect::<Vec<_>>(),
+            vec!["application/json"]
+        );
+    }
+
+    #[test]
+    fn test_overridden_by() {
+        let mut defaults = Headers::new();
+        defaults.insert("Authorization", "Bearer default");
+        defaults.insert("X-Client", "chipp");
+        defaults.insert("Expect", "100-continue");
+
+        let mut overrides = Headers::new();
+        overrides.insert("authorization", "Bearer request");
+        overrides.unset("Expect");
+
+        let headers = defaults.overridden_by(&overrides);
+
+        assert_eq!(
+            headers.get_all("Authorization").collect::<Vec<_>>(),
+            vec!["Bearer request"]
+        );
+        assert_eq!(headers.get("X-Client"), Some("chipp"));
+        assert_eq!(headers.get("Expect"), None);
+        assert!(headers.is_unset("expect"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut headers = Headers::new();
+        headers.insert("X-Valid", "value");
+        assert_eq!(headers.validate(), Ok(()));
+
+        headers.insert("X-Injected", "value\r\nX-Evil: 1");
+        assert_eq!(headers.validate(), Err("X-Injected".to_string()));
+
+        let mut headers = Headers::new();
+        headers.insert("X-Bad\r\nName", "value");
+        assert_eq!(headers.validate(), Err("X-Bad\r\nName".to_string()));
+
+        let mut headers = Headers::new();
+        headers.unset("Bad Name");
+        assert_eq!(headers.validate(), Err("Bad Name".to_string()));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let headers: Headers = [("Content-Type", "text/plain")].into_iter().collect();
+
+        assert_eq!(headers.get("content-type"), Some("text/plain"));
+        assert_eq!(headers.get("CONTENT-TYPE"), Some("text/plain"));
+        assert!(!headers.contains("Location"));
+    }
+
+    #[test]
+    fn test_multiple_values() {
+        let headers: Headers = [
+            ("Set-Cookie", "a=1"),
+            ("X-Other", "x"),
+            ("set-cookie", "b=2"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(headers.get("Set-Cookie"), Some("a=1"));
+        assert_eq!(
+            headers.get_all("Set-Cookie").collect::<Vec<_>>(),
+            vec!["a=1", "b=2"]
+        );
+    }
+}
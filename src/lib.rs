@@ -1,12 +1,14 @@
-use std::thread;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
 use std::{borrow::Borrow, str};
 
-use futures_channel::oneshot;
-
-use ::curl::easy::{Easy, Form, List};
+use ::curl::easy::{Easy, List};
 use url::Url;
 
-use log::trace;
+mod driver;
+use driver::{Driver, Reservation, Resume};
 
 mod hexdump;
 
@@ -14,7 +16,19 @@ mod request;
 pub use request::{HttpMethod, Request};
 
 mod response;
-pub use response::Response;
+pub use response::{Response, ResponseHead};
+
+mod headers;
+pub use headers::Headers;
+
+mod body;
+pub use body::Body;
+
+mod cookie;
+pub use cookie::{Cookie, CookieJar};
+
+mod multipart;
+pub use multipart::{Multipart, Part};
 
 pub mod curl {
     pub use ::curl::*;
@@ -22,32 +36,95 @@ pub mod curl {
 
 pub mod json;
 
+pub mod blocking;
+
+mod builder;
+pub use builder::RequestBuilder;
+
+mod stream;
+pub use stream::{BodyStream, StreamingResponse};
+
+mod download;
+
 mod error;
-pub use error::{Error, ErrorKind, UrlParseError};
+pub use error::{Error, ErrorKind, InvalidMethod, TimeoutPhase, UrlParseError};
+
+mod timeout;
+pub use timeout::Timeouts;
+
+mod progress;
+pub use progress::Progress;
+
+mod transport;
+pub use transport::{BoxFuture, Transport};
+
+mod middleware;
+pub use middleware::{Middleware, Next};
 
-pub trait Interceptor {
-    fn modify(&self, easy: &mut Easy, request: &Request);
-    fn add_headers(&self, headers: &mut List, request: &Request);
+#[cfg(feature = "tower")]
+mod service;
+
+pub mod mock;
+
+pub mod cassette;
+
+mod proxy;
+pub use proxy::{NoProxy, Proxy, ProxyConfig};
+
+mod tls;
+use tls::Tls;
+pub use tls::{Certificate, Identity, TlsConfig, TlsVersion};
+
+mod redirect;
+pub use redirect::{Redirect, RedirectAttempt, RedirectPolicy};
+
+mod retry;
+use retry::RetryCount;
+pub use retry::{ExponentialBackoff, RetryContext, RetryPolicy};
+
+pub trait Interceptor: Send + Sync {
+    fn intercept(&self, _request: &mut Request) {}
+
+    // escape hatches for curl specific tweaks, only used by the curl transport
+    fn modify(&self, _easy: &mut Easy, _request: &Request) {}
+    fn add_headers(&self, _headers: &mut List, _request: &Request) {}
 }
 
+#[derive(Clone, Copy)]
 pub struct NoInterceptor;
 
-impl Interceptor for NoInterceptor {
-    fn modify(&self, _: &mut Easy, _: &Request) {}
-    fn add_headers(&self, _: &mut List, _: &Request) {}
+impl Interceptor for NoInterceptor {}
+
+#[derive(Clone)]
+pub struct FnInterceptor<F>(pub F);
+
+impl<F: Fn(&mut Request) + Send + Sync> Interceptor for FnInterceptor<F> {
+    fn intercept(&self, request: &mut Request) {
+        (self.0)(request)
+    }
 }
 
-impl<T: Fn(&mut Easy, &Request)> Interceptor for T {
+impl<T: Fn(&mut Easy, &Request) + Send + Sync> Interceptor for T {
     fn modify(&self, easy: &mut Easy, request: &Request) {
         self(easy, request)
     }
-    fn add_headers(&self, _: &mut List, _: &Request) {}
 }
 
+#[derive(Clone)]
 pub struct HttpClient<I: Interceptor> {
     base_url: Url,
-    default_headers: Option<Vec<(String, String)>>,
+    default_headers: Headers,
     interceptor: I,
+    timeouts: Timeouts,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    transport: Option<Arc<dyn Transport>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    cookie_jar: Option<CookieJar>,
+    redirect_policy: RedirectPolicy,
+    proxy: Option<ProxyConfig>,
+    tls: Option<Arc<Tls>>,
+    driver: Arc<Driver>,
+    reservation: Reservation,
 }
 
 impl HttpClient<NoInterceptor> {
@@ -58,8 +135,18 @@ impl HttpClient<NoInterceptor> {
         let base_url = Url::parse(base_url.as_ref())?;
         Ok(HttpClient {
             base_url,
-            default_headers: None,
+            default_headers: Headers::new(),
             interceptor: NoInterceptor,
+            timeouts: Timeouts::default(),
+            retry_policy: None,
+            transport: None,
+            middleware: vec![],
+            cookie_jar: None,
+            redirect_policy: RedirectPolicy::None,
+            proxy: None,
+            tls: None,
+            driver: Arc::new(Driver::new()),
+            reservation: Reservation::default(),
         })
     }
 }
@@ -73,6 +160,16 @@ impl<X: Interceptor> HttpClient<X> {
             base_url: self.base_url,
             default_headers: self.default_headers,
             interceptor,
+            timeouts: self.timeouts,
+            retry_policy: self.retry_policy,
+            transport: self.transport,
+            middleware: self.middleware,
+            cookie_jar: self.cookie_jar,
+            redirect_policy: self.redirect_policy,
+            proxy: self.proxy,
+            tls: self.tls,
+            driver: self.driver,
+            reservation: self.reservation,
         }
     }
 
@@ -83,14 +180,58 @@ impl<X: Interceptor> HttpClient<X> {
         K: ToString,
         V: ToString,
     {
-        let mut default_headers = vec![];
+        let mut default_headers = Headers::new();
 
         for pair in headers.into_iter() {
             let (k, v) = pair.borrow();
-            default_headers.push((k.to_string(), v.to_string()));
+            default_headers.insert(k.to_string(), v.to_string());
         }
 
-        self.default_headers = Some(default_headers)
+        self.default_headers = default_headers
+    }
+
+    pub fn default_headers_mut(&mut self) -> &mut Headers {
+        &mut self.default_headers
+    }
+
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.timeouts.connect = Some(timeout)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeouts.total = Some(timeout)
+    }
+
+    pub fn set_low_speed_timeout(&mut self, bytes_per_second: u32, time: Duration) {
+        self.timeouts.low_speed = Some((bytes_per_second, time))
+    }
+
+    pub fn set_retry_policy<P: RetryPolicy + 'static>(&mut self, policy: P) {
+        self.retry_policy = Some(Arc::new(policy))
+    }
+
+    pub fn set_max_connections(&mut self, max: usize) {
+        self.driver.set_max_connections(max)
+    }
+
+    pub fn set_redirect_policy(&mut self, policy: RedirectPolicy) {
+        self.redirect_policy = policy
+    }
+
+    pub fn set_proxy(&mut self, proxy: ProxyConfig) {
+        self.proxy = Some(proxy)
+    }
+
+    pub fn set_tls_config(&mut self, tls: TlsConfig) {
+        self.tls = Some(Arc::new(Tls::new(tls)))
+    }
+
+    pub fn set_cookie_jar(&mut self, cookie_jar: CookieJar) {
+        self.cookie_jar = Some(cookie_jar)
+    }
+
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_ref()
     }
 }
 
@@ -105,120 +246,147 @@ impl<X: Interceptor> HttpClient<X> {
         url
     }
 
-    pub async fn perform_request<R: Send + 'static, P>(
+    pub(crate) fn prepare_easy(
         &self,
-        request: Request,
-        parse: P,
-    ) -> Result<R, Error>
-    where
-        P: Fn(Request, Response) -> Result<R, Error> + Send + 'static,
-    {
-        let (tx, rx) = oneshot::channel::<Result<R, Error>>();
+        request: &Request,
+        resume: &Arc<Resume>,
+    ) -> Result<(Easy, Timeouts), ErrorKind> {
+        check_request(request)?;
+
         let mut easy = Easy::new();
         easy.url(request.url.as_str()).unwrap();
 
-        match request.method {
+        match &request.method {
             HttpMethod::Get => (),
             HttpMethod::Post => easy.post(true).unwrap(),
-            HttpMethod::Put => easy.custom_request("PUT").unwrap(),
-            HttpMethod::Delete => easy.custom_request("DELETE").unwrap(),
+            HttpMethod::Head => easy.nobody(true).unwrap(),
+            method => easy.custom_request(method.as_str()).unwrap(),
         }
 
-        if let Some(form_params) = &request.form {
-            let mut form = Form::new();
+        if let Some(form) = &request.form {
+            form.validate().map_err(ErrorKind::InvalidHeader)?;
+        }
 
-            for (k, v) in form_params {
-                let mut part = form.part(&k);
-                part.contents(v.as_bytes());
-                part.add().unwrap();
+        let body = request
+            .form
+            .as_ref()
+            .map(Multipart::body)
+            .or(request.body.as_ref());
+        if let Some(body) = body {
+            body.configure(&mut easy, resume);
+
+            // setting a body switches curl to POST
+            if request.method != HttpMethod::Post {
+                easy.custom_request(request.method.as_str()).unwrap();
             }
+        }
+
+        let mut headers = self.request_headers(request);
 
-            easy.httppost(form).unwrap();
+        let chunked = body.is_some_and(|body| body.len().is_none());
+        if chunked && !headers.contains("Transfer-Encoding") {
+            headers.insert("Transfer-Encoding", "chunked");
         }
 
-        if let Some(body) = &request.body {
-            easy.post_field_size(body.len() as u64).unwrap();
-            easy.post_fields_copy(&body).unwrap();
+        headers.validate().map_err(ErrorKind::InvalidHeader)?;
+
+        let mut headers = headers_to_list(&headers);
+
+        // interceptors are written for the origin they were set up for, and could put
+        // back the credentials a cross-origin redirect stripped
+        if !request.cross_origin {
+            self.interceptor.add_headers(&mut headers, request);
         }
+        easy.http_headers(headers).unwrap();
 
-        let mut headers = List::new();
+        let timeouts = self.timeouts.overridden_by(&request.timeouts);
+        timeouts.apply(&mut easy);
 
-        if let Some(default_headers) = &self.default_headers {
-            add_headers_to_list(default_headers, &mut headers);
+        match self.proxy_for(&request.url) {
+            Some(proxy) => easy.proxy(proxy.url.as_str()).unwrap(),
+            // proxies from the environment are opt-in through ProxyConfig::from_env
+            None => easy.proxy("").unwrap(),
         }
 
-        if let Some(request_headers) = &request.headers {
-            add_headers_to_list(request_headers, &mut headers);
+        if let Some(tls) = &self.tls {
+            tls.apply(&mut easy)?;
         }
 
-        self.interceptor.add_headers(&mut headers, &request);
-        easy.http_headers(headers).unwrap();
+        if let Some(hook) = &request.progress {
+            progress::report_progress(&mut easy, hook);
+        }
+
+        if !request.cross_origin {
+            self.interceptor.modify(&mut easy, request);
+        }
+
+        Ok((easy, timeouts))
+    }
 
-        self.interceptor.modify(&mut easy, &request);
-
-        thread::spawn(move || {
-            let mut body = Vec::new();
-            let mut headers = Vec::new();
-
-            let mut attempts = 0;
-            let mut transfer_error = None;
-
-            loop {
-                attempts += 1;
-
-                let mut transfer = easy.transfer();
-                transfer
-                    .write_function(|data| {
-                        body.extend_from_slice(data);
-                        Ok(data.len())
-                    })
-                    .unwrap();
-
-                transfer
-                    .header_function(|header| {
-                        headers.push(str::from_utf8(header).unwrap().trim_end().to_string());
-                        true
-                    })
-                    .unwrap();
-
-                match transfer.perform() {
-                    Ok(()) => break,
-                    Err(err) => {
-                        if request.retry_count.is_none() || request.retry_count == Some(attempts) {
-                            transfer_error = Some(err);
-                            break;
-                        } else {
-                            let delay = delay_for_attempt(attempts);
-
-                            trace!(
-                                "request {:?} finished with error, will repeat in {} ms",
-                                request.url.as_str(),
-                                delay
-                            );
-
-                            std::thread::sleep(std::time::Duration::from_millis(delay))
-                        }
-                    }
+    pub(crate) fn request_headers(&self, request: &Request) -> Headers {
+        let mut headers = self.default_headers.overridden_by(&request.headers);
+
+        if let Some(form) = &request.form {
+            if !headers.contains("Content-Type") {
+                headers.insert("Content-Type", form.content_type());
+            }
+        }
+
+        if let Some(cookie_jar) = &self.cookie_jar {
+            if !headers.contains("Cookie") && !headers.is_unset("Cookie") {
+                if let Some(cookies) = cookie_jar.header_for(&request.url) {
+                    headers.insert("Cookie", cookies);
                 }
             }
+        }
+
+        headers
+    }
+
+    pub(crate) fn store_cookies(&self, request: &Request, response: &Response) {
+        if let Some(cookie_jar) = &self.cookie_jar {
+            cookie_jar.store(&request.url, response);
+        }
+    }
+
+    pub(crate) fn proxy_for(&self, url: &Url) -> Option<&Proxy> {
+        self.proxy.as_ref()?.proxy_for(url)
+    }
+
+    pub(crate) fn redirect_policy_for(&self, request: &Request) -> RedirectPolicy {
+        match &request.redirect_policy {
+            Some(policy) => policy.clone(),
+            None => self.redirect_policy.clone(),
+        }
+    }
 
-            if let Some(err) = transfer_error {
-                let _ = tx.send(Err((request, err).into()));
-            } else {
-                let status_code = easy.response_code().unwrap();
-
-                let _ = tx.send(parse(
-                    request,
-                    Response {
-                        status_code,
-                        body,
-                        headers,
-                    },
-                ));
+    pub(crate) fn retry_policy_for(&self, request: &Request) -> Option<Arc<dyn RetryPolicy>> {
+        match (&request.retry_policy, request.retry_count) {
+            (Some(policy), _) => Some(policy.clone()),
+            (None, Some(retry_count)) => {
+                Some(Arc::new(RetryCount(retry_count)) as Arc<dyn RetryPolicy>)
             }
-        });
+            (None, None) => self.retry_policy.clone(),
+        }
+    }
 
-        rx.await.unwrap()
+    pub async fn perform_request<R: Send + 'static, P>(
+        &self,
+        mut request: Request,
+        parse: P,
+    ) -> Result<R, Error>
+    where
+        P: Fn(Request, Response) -> Result<R, Error> + Send + 'static,
+    {
+        let outcome = self.dispatch(&mut request).await;
+
+        match outcome {
+            Ok(response) => parse(request, response),
+            Err(kind) => Err(Error {
+                request: Box::new(request),
+                kind,
+            }),
+        }
     }
 }
 
@@ -263,34 +431,53 @@ pub fn parse_void(req: Request, res: Response) -> Result<(), Error> {
     }
 }
 
-fn add_headers_to_list<H, K, V>(headers: H, list: &mut List)
-where
-    H: IntoIterator,
-    H::Item: Borrow<(K, V)>,
-    K: AsRef<str>,
-    V: AsRef<str>,
-{
-    for pair in headers {
-        let (header, value) = pair.borrow();
-        list.append(&format!("{}: {}", header.as_ref(), value.as_ref()))
-            .unwrap();
+pub(crate) fn check_request(request: &Request) -> Result<(), ErrorKind> {
+    // the method goes into the request line verbatim
+    if !request.method.is_valid() {
+        return Err(ErrorKind::InvalidMethod(request.method.clone()));
+    }
+
+    // a HEAD response has no body to read, so curl would never send one either
+    let has_body = request.body.is_some() || request.form.is_some();
+
+    if has_body && request.method == HttpMethod::Head {
+        Err(ErrorKind::BodyNotAllowed(request.method.clone()))
+    } else {
+        Ok(())
     }
 }
 
-fn delay_for_attempt(attempt: u8) -> u64 {
-    let delay = (attempt as f64) * 0.5 + 1_f64;
-    let delay = delay.exp() * 100_f64;
-    delay as u64
+fn headers_to_list(headers: &Headers) -> List {
+    let mut list = List::new();
+
+    for (name, value) in headers.iter() {
+        if value.is_empty() {
+            list.append(&format!("{};", name)).unwrap();
+        } else {
+            list.append(&format!("{}: {}", name, value)).unwrap();
+        }
+    }
+
+    for name in headers.unset_names() {
+        list.append(&format!("{}:", name)).unwrap();
+    }
+
+    list
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// std has no rng, but every RandomState is seeded with fresh keys
+pub(crate) fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    hasher.finish()
+}
 
-    #[test]
-    fn test_delay() {
-        assert_eq!(delay_for_attempt(1), 448);
-        assert_eq!(delay_for_attempt(2), 738);
-        assert_eq!(delay_for_attempt(3), 1218);
+pub(crate) fn transfer_error(err: ::curl::Error, easy: &Easy, timeouts: &Timeouts) -> ErrorKind {
+    if err.is_operation_timedout() {
+        ErrorKind::Timeout {
+            phase: timeouts.phase(easy),
+        }
+    } else {
+        ErrorKind::CurlError(err)
     }
 }
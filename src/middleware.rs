@@ -0,0 +1,208 @@
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use crate::redirect::{Hop, Redirects};
+use crate::transport::Exchange;
+use crate::{BoxFuture, ErrorKind, HttpClient, Interceptor, Request, Response, RetryContext};
+
+pub trait Middleware: Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>>;
+}
+
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    chain: &'a [Arc<dyn Middleware>],
+    endpoint: &'a dyn Endpoint,
+}
+
+pub(crate) trait Endpoint: Send + Sync {
+    fn send<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<Response, ErrorKind>>;
+}
+
+impl<'a> Next<'a> {
+    pub fn run<'r>(self, request: &'r mut Request) -> BoxFuture<'r, Result<Response, ErrorKind>>
+    where
+        'a: 'r,
+    {
+        match self.chain.split_first() {
+            Some((middleware, chain)) => middleware.handle(
+                request,
+                Next {
+                    chain,
+                    endpoint: self.endpoint,
+                },
+            ),
+            None => self.endpoint.send(request),
+        }
+    }
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.add_middleware(middleware);
+        self
+    }
+
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Arc::new(middleware))
+    }
+
+    pub(crate) async fn dispatch(&self, request: &mut Request) -> Result<Response, ErrorKind> {
+        self.dispatch_to(request, self).await
+    }
+
+    // streams, downloads and the blocking client end the chain with their own endpoint
+    pub(crate) async fn dispatch_to(
+        &self,
+        request: &mut Request,
+        endpoint: &dyn Endpoint,
+    ) -> Result<Response, ErrorKind> {
+        self.interceptor.intercept(request);
+
+        let next = Next {
+            chain: &self.middleware,
+            endpoint,
+        };
+
+        next.run(request).await
+    }
+}
+
+// middleware may send the same request more than once
+pub(crate) fn rewind(request: &Request) -> Result<(), ErrorKind> {
+    if request.rewind_body() {
+        Ok(())
+    } else {
+        Err(ErrorKind::Io(io::Error::other(
+            "request body can't be sent again",
+        )))
+    }
+}
+
+// how a hop is sent: the client, streams, downloads and the blocking client each bring their
+// own, while retries, cookies and redirects are handled the same for all of them
+pub(crate) trait Attempt: Send {
+    // once per hop, so errors here end the request instead of being retried
+    fn prepare(&mut self, request: &mut Request) -> Result<(), ErrorKind>;
+
+    fn perform<'a>(
+        &'a mut self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>>;
+
+    fn pause(&self, delay: Duration) -> BoxFuture<'_, ()>;
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub(crate) async fn send_hops(
+        &self,
+        request: &mut Request,
+        attempt: &mut dyn Attempt,
+    ) -> Result<Response, ErrorKind> {
+        rewind(request)?;
+
+        let mut redirects = Redirects::new(self.redirect_policy_for(request));
+        let mut redirected = None;
+
+        loop {
+            let current = redirected.as_mut().unwrap_or(&mut *request);
+            let response = self.send_with_retries(current, attempt).await?;
+
+            match redirects.follow(current, response)? {
+                Hop::Follow(next) => redirected = Some(*next),
+                Hop::Stop(response) => return Ok(redirects.finish(current, *response)),
+            }
+        }
+    }
+
+    async fn send_with_retries(
+        &self,
+        request: &mut Request,
+        attempt: &mut dyn Attempt,
+    ) -> Result<Response, ErrorKind> {
+        attempt.prepare(request)?;
+        let retry_policy = self.retry_policy_for(request);
+
+        let started = Instant::now();
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let outcome = attempt.perform(request).await;
+
+            if let Ok(response) = &outcome {
+                self.store_cookies(request, response);
+            }
+
+            let delay = retry_policy.as_ref().and_then(|policy| {
+                policy.retry_delay(&RetryContext {
+                    request,
+                    attempt: attempts,
+                    elapsed: started.elapsed(),
+                    outcome: outcome.as_ref(),
+                })
+            });
+
+            match delay {
+                Some(delay) if request.rewind_body() => {
+                    trace!(
+                        "request {:?} attempt {} failed, will repeat in {} ms",
+                        request.url.as_str(),
+                        attempts,
+                        delay.as_millis()
+                    );
+
+                    attempt.pause(delay).await;
+                }
+                _ => break outcome,
+            }
+        }
+    }
+}
+
+struct Exchanges<'c, X: Interceptor> {
+    client: &'c HttpClient<X>,
+    exchange: Option<Exchange<'c>>,
+}
+
+impl<X: Interceptor> Attempt for Exchanges<'_, X> {
+    fn prepare(&mut self, request: &mut Request) -> Result<(), ErrorKind> {
+        self.exchange = Some(self.client.exchange(request)?);
+        Ok(())
+    }
+
+    fn perform<'a>(
+        &'a mut self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        let client = self.client;
+        let exchange = self.exchange.as_mut().unwrap();
+
+        Box::pin(exchange.perform(&client.driver, client.reservation.take(), request))
+    }
+
+    fn pause(&self, delay: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(self.client.driver.delay(delay))
+    }
+}
+
+impl<X: Interceptor> Endpoint for HttpClient<X> {
+    fn send<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let mut exchanges = Exchanges {
+                client: self,
+                exchange: None,
+            };
+
+            self.send_hops(request, &mut exchanges).await
+        })
+    }
+}
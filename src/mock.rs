@@ -0,0 +1,366 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{BoxFuture, ErrorKind, HttpMethod, Request, Response, Transport};
+
+type Responder = dyn Fn(&Request) -> Result<Response, ErrorKind> + Send + Sync;
+type BodyPredicate = dyn Fn(&[u8]) -> bool + Send + Sync;
+
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    expectations: Vec<Expectation>,
+    unmatched: Vec<String>,
+}
+
+pub struct MockExpectation {
+    mock: MockTransport,
+    method: HttpMethod,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<BodyMatcher>,
+    times: Option<usize>,
+}
+
+struct Expectation {
+    method: HttpMethod,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<BodyMatcher>,
+    times: Option<usize>,
+    calls: usize,
+    responder: Arc<Responder>,
+}
+
+enum BodyMatcher {
+    Bytes(Vec<u8>),
+    Json(Value),
+    Custom(Arc<BodyPredicate>),
+}
+
+impl MockTransport {
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    pub fn expect<P: ToString>(&self, method: HttpMethod, path: P) -> MockExpectation {
+        MockExpectation {
+            mock: self.clone(),
+            method,
+            path: path.to_string(),
+            query: vec![],
+            headers: vec![],
+            body: None,
+            times: None,
+        }
+    }
+
+    pub fn unmet(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+
+        let unmet = state
+            .expectations
+            .iter()
+            .filter(|expectation| !expectation.is_met())
+            .map(|expectation| {
+                format!(
+                    "expected {}, called {} time(s)",
+                    expectation, expectation.calls
+                )
+            });
+
+        let unmatched = state
+            .unmatched
+            .iter()
+            .map(|request| format!("unexpected request {}", request));
+
+        unmet.chain(unmatched).collect()
+    }
+
+    pub fn assert_all_met(&self) {
+        let unmet = self.unmet();
+
+        if !unmet.is_empty() {
+            panic!("mock expectations were not met:\n  {}", unmet.join("\n  "));
+        }
+    }
+
+    async fn handle(&self, request: &Request) -> Result<Response, ErrorKind> {
+        let body = request
+            .read_body()
+            .await
+            .map_err(ErrorKind::Io)?
+            .unwrap_or_default();
+
+        let mut state = self.state.lock().unwrap();
+
+        let expectation = state
+            .expectations
+            .iter_mut()
+            .find(|expectation| expectation.matches(request, &body));
+
+        match expectation {
+            Some(expectation) => {
+                expectation.calls += 1;
+
+                let responder = expectation.responder.clone();
+                drop(state);
+
+                responder(request)
+            }
+            None => {
+                let description = format!("{} {}", request.method, request.url);
+                state.unmatched.push(description.clone());
+
+                Ok(canned(
+                    501,
+                    format!("no mock expectation matched {}", description),
+                ))
+            }
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(self.handle(request))
+    }
+}
+
+impl MockExpectation {
+    pub fn query<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header<H: ToString, V: ToString>(mut self, header: H, value: V) -> Self {
+        self.headers.push((header.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = Some(BodyMatcher::Bytes(body.into()));
+        self
+    }
+
+    pub fn json_body<J: Serialize>(mut self, json: &J) -> Self {
+        let json = serde_json::to_value(json).expect("valid json argument");
+        self.body = Some(BodyMatcher::Json(json));
+        self
+    }
+
+    pub fn body_matches<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&[u8]) -> bool + Send + Sync + 'static,
+    {
+        self.body = Some(BodyMatcher::Custom(Arc::new(predicate)));
+        self
+    }
+
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    pub fn respond<B: Into<Vec<u8>>>(self, status_code: u32, body: B) {
+        self.respond_with(canned(status_code, body))
+    }
+
+    pub fn respond_json<J: Serialize>(self, status_code: u32, json: &J) {
+        let body = serde_json::to_vec(json).expect("valid json argument");
+
+        let mut response = canned(status_code, body);
+        response.headers.insert("Content-Type", "application/json");
+
+        self.respond_with(response)
+    }
+
+    pub fn respond_with(self, response: Response) {
+        self.respond_fn(move |_| Ok(response.clone()))
+    }
+
+    pub fn respond_fn<F>(self, responder: F)
+    where
+        F: Fn(&Request) -> Result<Response, ErrorKind> + Send + Sync + 'static,
+    {
+        let expectation = Expectation {
+            method: self.method,
+            path: self.path,
+            query: self.query,
+            headers: self.headers,
+            body: self.body,
+            times: self.times,
+            calls: 0,
+            responder: Arc::new(responder),
+        };
+
+        self.mock
+            .state
+            .lock()
+            .unwrap()
+            .expectations
+            .push(expectation);
+    }
+}
+
+impl Expectation {
+    fn is_met(&self) -> bool {
+        match self.times {
+            Some(times) => self.calls == times,
+            None => self.calls > 0,
+        }
+    }
+
+    fn matches(&self, request: &Request, body: &[u8]) -> bool {
+        if self.times.is_some_and(|times| self.calls >= times) {
+            return false;
+        }
+
+        if request.method != self.method || request.url.path() != self.path {
+            return false;
+        }
+
+        let query_matches = self.query.iter().all(|(key, value)| {
+            request
+                .url
+                .query_pairs()
+                .any(|(k, v)| k == key.as_str() && v == value.as_str())
+        });
+
+        let headers_match = self
+            .headers
+            .iter()
+            .all(|(name, value)| request.headers.get_all(name).any(|v| v == value));
+
+        let body_matches = match &self.body {
+            None => true,
+            Some(BodyMatcher::Bytes(bytes)) => body == bytes.as_slice(),
+            Some(BodyMatcher::Json(json)) => {
+                serde_json::from_slice::<Value>(body).is_ok_and(|value| value == *json)
+            }
+            Some(BodyMatcher::Custom(predicate)) => predicate(body),
+        };
+
+        query_matches && headers_match && body_matches
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+
+        for (i, (key, value)) in self.query.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            write!(f, "{}{}={}", separator, key, value)?;
+        }
+
+        if let Some(times) = self.times {
+            write!(f, " {} time(s)", times)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn canned<B: Into<Vec<u8>>>(status_code: u32, body: B) -> Response {
+    Response {
+        status_code,
+        version: "HTTP/1.1".to_string(),
+        body: body.into(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Body;
+    use futures_executor::block_on;
+    use url::Url;
+
+    fn send(mock: &MockTransport, request: &Request) -> Response {
+        match block_on(mock.handle(request)) {
+            Ok(response) => response,
+            Err(_) => panic!("mock transport failed for {:?}", request),
+        }
+    }
+
+    fn request(method: HttpMethod, url: &str) -> Request {
+        let mut request = Request::new(Url::parse(url).unwrap());
+        request.set_method(method);
+        request
+    }
+
+    #[test]
+    fn test_matching() {
+        let mock = MockTransport::new();
+        mock.expect(HttpMethod::Get, "/users")
+            .query("page", "2")
+            .header("accept", "application/json")
+            .respond(200, "users");
+
+        let mut matching = request(HttpMethod::Get, "http://api.test/users?page=2&limit=5");
+        matching.add_header("Accept", "application/json");
+
+        let response = send(&mock, &matching);
+        assert_eq!(response.body, b"users");
+
+        let response = send(
+            &mock,
+            &request(HttpMethod::Get, "http://api.test/users?page=3"),
+        );
+        assert_eq!(response.status_code, 501);
+
+        assert_eq!(
+            mock.unmet(),
+            vec!["unexpected request GET http://api.test/users?page=3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_times() {
+        let mock = MockTransport::new();
+        mock.expect(HttpMethod::Delete, "/item")
+            .times(1)
+            .respond(204, "");
+
+        assert_eq!(
+            mock.unmet(),
+            vec!["expected DELETE /item 1 time(s), called 0 time(s)"]
+        );
+
+        let delete = request(HttpMethod::Delete, "http://api.test/item");
+        assert_eq!(send(&mock, &delete).status_code, 204);
+        assert!(mock.unmet().is_empty());
+
+        assert_eq!(send(&mock, &delete).status_code, 501);
+    }
+
+    #[test]
+    fn test_body_matchers() {
+        let mock = MockTransport::new();
+        mock.expect(HttpMethod::Post, "/json")
+            .json_body(&serde_json::json!({ "a": 1, "b": [true] }))
+            .respond(201, "");
+        mock.expect(HttpMethod::Post, "/streamed")
+            .body("streamed body")
+            .respond(202, "");
+
+        let mut json = request(HttpMethod::Post, "http://api.test/json");
+        json.set_body(r#"{ "b": [true], "a": 1 }"#);
+        assert_eq!(send(&mock, &json).status_code, 201);
+
+        let mut streamed = request(HttpMethod::Post, "http://api.test/streamed");
+        streamed.set_body(Body::from_reader(&b"streamed body"[..], None));
+        assert_eq!(send(&mock, &streamed).status_code, 202);
+    }
+}
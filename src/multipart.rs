@@ -0,0 +1,353 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+
+use crate::body::Source;
+use crate::{random, Body, Headers};
+
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<Part>,
+    encoded: OnceLock<Body>,
+}
+
+pub struct Part {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    headers: Headers,
+    body: Body,
+}
+
+impl Multipart {
+    pub fn new() -> Multipart {
+        Multipart {
+            boundary: format!("chipp-http-{:016x}{:016x}", random(), random()),
+            parts: vec![],
+            encoded: OnceLock::new(),
+        }
+    }
+
+    pub fn text<N: ToString, V: ToString>(self, name: N, value: V) -> Multipart {
+        self.part(Part::text(name, value))
+    }
+
+    pub fn bytes<N, F, B>(self, name: N, file_name: F, bytes: B) -> Multipart
+    where
+        N: ToString,
+        F: ToString,
+        B: Into<Vec<u8>>,
+    {
+        self.part(Part::bytes(name, bytes).file_name(file_name))
+    }
+
+    pub fn file<N: ToString, P: AsRef<Path>>(self, name: N, path: P) -> io::Result<Multipart> {
+        Ok(self.part(Part::file(name, path)?))
+    }
+
+    pub fn part(mut self, part: Part) -> Multipart {
+        self.parts.push(part);
+        self.encoded = OnceLock::new();
+        self
+    }
+
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    pub fn len(&self) -> Option<u64> {
+        let mut length = self.closing().len() as u64;
+
+        for part in &self.parts {
+            length += part.head(&self.boundary).len() as u64 + part.body.len()? + 2;
+        }
+
+        Some(length)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        self.parts.iter().try_for_each(Part::validate)
+    }
+
+    pub(crate) fn rewind(&self) -> bool {
+        self.encoded.get().is_none_or(Body::rewind)
+    }
+
+    pub(crate) fn body(&self) -> &Body {
+        self.encoded.get_or_init(|| self.encode())
+    }
+
+    fn encode(&self) -> Body {
+        let mut segments = vec![];
+
+        for part in &self.parts {
+            segments.push(Body::from(part.head(&self.boundary)).into_source());
+            segments.push(part.body.share().into_source());
+            segments.push(Body::from("\r\n").into_source());
+        }
+
+        segments.push(Body::from(self.closing()).into_source());
+
+        Body::streamed(
+            MultipartSource {
+                segments,
+                current: 0,
+            },
+            self.len(),
+        )
+    }
+
+    fn closing(&self) -> String {
+        format!("--{}--\r\n", self.boundary)
+    }
+}
+
+impl Default for Multipart {
+    fn default() -> Multipart {
+        Multipart::new()
+    }
+}
+
+impl Part {
+    pub fn text<N: ToString, V: ToString>(name: N, value: V) -> Part {
+        Part::body(name, value.to_string())
+    }
+
+    pub fn bytes<N: ToString, B: Into<Vec<u8>>>(name: N, bytes: B) -> Part {
+        Part::body(name, bytes.into())
+    }
+
+    pub fn file<N: ToString, P: AsRef<Path>>(name: N, path: P) -> io::Result<Part> {
+        let path = path.as_ref();
+
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+
+        let mut part = Part::body(name, Body::from_seekable(file, Some(length))?);
+        part.file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned());
+
+        Ok(part)
+    }
+
+    pub fn body<N: ToString, B: Into<Body>>(name: N, body: B) -> Part {
+        Part {
+            name: name.to_string(),
+            file_name: None,
+            content_type: None,
+            headers: Headers::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn file_name<F: ToString>(mut self, file_name: F) -> Part {
+        self.file_name = Some(file_name.to_string());
+        self
+    }
+
+    pub fn content_type<C: ToString>(mut self, content_type: C) -> Part {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    pub fn header<H: ToString, V: ToString>(mut self, header: H, value: V) -> Part {
+        self.headers.append(header, value);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut headers = self.headers.clone();
+        if let Some(content_type) = &self.content_type {
+            headers.append("Content-Type", content_type);
+        }
+
+        headers.validate()
+    }
+
+    fn head(&self, boundary: &str) -> String {
+        let mut head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            boundary,
+            escape(&self.name)
+        );
+
+        if let Some(file_name) = &self.file_name {
+            head.push_str(&format!("; filename=\"{}\"", escape(file_name)));
+        }
+        head.push_str("\r\n");
+
+        let content_type = match (&self.content_type, &self.file_name) {
+            (Some(content_type), _) => Some(content_type.as_str()),
+            (None, Some(_)) => Some("application/octet-stream"),
+            (None, None) => None,
+        };
+
+        if let Some(content_type) = content_type {
+            head.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+
+        for (name, value) in self.headers.iter() {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        head.push_str("\r\n");
+        head
+    }
+}
+
+impl fmt::Debug for Multipart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.parts).finish()
+    }
+}
+
+impl fmt::Debug for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Part");
+        debug.field("name", &self.name);
+
+        if let Some(file_name) = &self.file_name {
+            debug.field("file_name", file_name);
+        }
+
+        if let Some(content_type) = &self.content_type {
+            debug.field("content_type", content_type);
+        }
+
+        debug.field("body", &self.body).finish()
+    }
+}
+
+struct MultipartSource {
+    segments: Vec<Box<dyn Source>>,
+    current: usize,
+}
+
+impl Source for MultipartSource {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        while let Some(segment) = self.segments.get_mut(self.current) {
+            match segment.poll_read(cx, buf) {
+                Poll::Ready(Ok(0)) if !buf.is_empty() => self.current += 1,
+                other => return other,
+            }
+        }
+
+        Poll::Ready(Ok(0))
+    }
+
+    fn rewind(&mut self) -> bool {
+        self.current = 0;
+        self.segments.iter_mut().all(|segment| segment.rewind())
+    }
+
+    // one async part is enough for the whole form to be polled by the caller
+    fn is_async(&self) -> bool {
+        self.segments.iter().any(|segment| segment.is_async())
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::task::Waker;
+
+    fn encode(multipart: &Multipart) -> String {
+        read_all(&mut multipart.encode().into_source())
+    }
+
+    fn read_all(source: &mut Box<dyn Source>) -> String {
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut encoded = vec![];
+        let mut buf = [0; 7];
+        while let Poll::Ready(Ok(read)) = source.poll_read(&mut cx, &mut buf) {
+            if read == 0 {
+                break;
+            }
+            encoded.extend_from_slice(&buf[..read]);
+        }
+
+        String::from_utf8(encoded).unwrap()
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut multipart = Multipart::new()
+            .text("title", "hello")
+            .bytes("upload", "a \"b\".txt", "contents")
+            .part(
+                Part::text("meta", "{}")
+                    .content_type("application/json")
+                    .header("X-Part", "1"),
+            );
+        multipart.boundary = "XYZ".to_string();
+
+        let expected = "--XYZ\r\n\
+            Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+            hello\r\n\
+            --XYZ\r\n\
+            Content-Disposition: form-data; name=\"upload\"; filename=\"a %22b%22.txt\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n\
+            contents\r\n\
+            --XYZ\r\n\
+            Content-Disposition: form-data; name=\"meta\"\r\n\
+            Content-Type: application/json\r\n\
+            X-Part: 1\r\n\r\n\
+            {}\r\n\
+            --XYZ--\r\n";
+
+        assert_eq!(encode(&multipart), expected);
+        assert_eq!(multipart.len(), Some(expected.len() as u64));
+        assert_eq!(
+            multipart.content_type(),
+            "multipart/form-data; boundary=XYZ"
+        );
+    }
+
+    #[test]
+    fn test_unknown_length() {
+        let multipart = Multipart::new()
+            .text("title", "hello")
+            .part(Part::body("data", Body::from_reader(io::empty(), None)));
+
+        assert_eq!(multipart.len(), None);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let multipart = Multipart::new().text("title", "hello");
+        let mut source = multipart.encode().into_source();
+
+        let first = read_all(&mut source);
+        assert!(source.rewind());
+        assert_eq!(read_all(&mut source), first);
+    }
+}
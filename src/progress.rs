@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use curl::easy::Easy;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub downloaded: u64,
+    pub download_total: Option<u64>,
+    pub uploaded: u64,
+    pub upload_total: Option<u64>,
+}
+
+pub(crate) type ProgressFn = dyn Fn(Progress) + Send + Sync;
+
+impl Progress {
+    fn from_curl(dltotal: f64, dlnow: f64, ultotal: f64, ulnow: f64) -> Progress {
+        // curl reports zero for totals it doesn't know yet
+        let total = |value: f64| (value > 0.0).then_some(value as u64);
+
+        Progress {
+            downloaded: dlnow as u64,
+            download_total: total(dltotal),
+            uploaded: ulnow as u64,
+            upload_total: total(ultotal),
+        }
+    }
+}
+
+pub(crate) fn report_progress(easy: &mut Easy, hook: &Arc<ProgressFn>) {
+    let hook = hook.clone();
+    let mut last = Progress::default();
+
+    easy.progress(true).unwrap();
+    easy.progress_function(move |dltotal, dlnow, ultotal, ulnow| {
+        let progress = Progress::from_curl(dltotal, dlnow, ultotal, ulnow);
+
+        if progress != last {
+            last = progress;
+            hook(progress);
+        }
+
+        true
+    })
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_curl() {
+        assert_eq!(
+            Progress::from_curl(0.0, 512.0, 2048.0, 1024.0),
+            Progress {
+                downloaded: 512,
+                download_total: None,
+                uploaded: 1024,
+                upload_total: Some(2048),
+            }
+        );
+    }
+}
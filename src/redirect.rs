@@ -0,0 +1,337 @@
+use std::fmt;
+use std::sync::Arc;
+
+use url::Url;
+
+use crate::{ErrorKind, HttpMethod, Request, Response};
+
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Clone, Default)]
+pub enum RedirectPolicy {
+    #[default]
+    None,
+    Limited(usize),
+    SameOrigin,
+    Custom(Arc<dyn Fn(&RedirectAttempt) -> bool + Send + Sync>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    pub url: Url,
+    pub status_code: u32,
+}
+
+pub struct RedirectAttempt<'a> {
+    pub status_code: u32,
+    pub from: &'a Url,
+    pub to: &'a Url,
+    pub history: &'a [Redirect],
+}
+
+impl RedirectPolicy {
+    pub fn custom<F>(policy: F) -> RedirectPolicy
+    where
+        F: Fn(&RedirectAttempt) -> bool + Send + Sync + 'static,
+    {
+        RedirectPolicy::Custom(Arc::new(policy))
+    }
+
+    fn allows(&self, attempt: &RedirectAttempt) -> Result<bool, ()> {
+        let limit = match self {
+            RedirectPolicy::None => return Ok(false),
+            RedirectPolicy::Limited(limit) => *limit,
+            RedirectPolicy::SameOrigin | RedirectPolicy::Custom(_) => MAX_REDIRECTS,
+        };
+
+        if attempt.history.len() >= limit {
+            return Err(());
+        }
+
+        Ok(match self {
+            RedirectPolicy::SameOrigin => attempt.from.origin() == attempt.to.origin(),
+            RedirectPolicy::Custom(policy) => policy(attempt),
+            _ => true,
+        })
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedirectPolicy::None => write!(f, "None"),
+            RedirectPolicy::Limited(limit) => f.debug_tuple("Limited").field(limit).finish(),
+            RedirectPolicy::SameOrigin => write!(f, "SameOrigin"),
+            RedirectPolicy::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+pub(crate) enum Hop {
+    Follow(Box<Request>),
+    Stop(Box<Response>),
+}
+
+pub(crate) struct Redirects {
+    policy: RedirectPolicy,
+    history: Vec<Redirect>,
+}
+
+impl Redirects {
+    pub(crate) fn new(policy: RedirectPolicy) -> Redirects {
+        Redirects {
+            policy,
+            history: vec![],
+        }
+    }
+
+    // the next hop is a copy, so the caller's request keeps pointing at the original URL
+    pub(crate) fn follow(
+        &mut self,
+        request: &Request,
+        response: Response,
+    ) -> Result<Hop, ErrorKind> {
+        let Some(location) = location(&request.url, &response) else {
+            return Ok(Hop::Stop(Box::new(response)));
+        };
+
+        let attempt = RedirectAttempt {
+            status_code: response.status_code,
+            from: &request.url,
+            to: &location,
+            history: &self.history,
+        };
+
+        match self.policy.allows(&attempt) {
+            Ok(true) => (),
+            Ok(false) => return Ok(Hop::Stop(Box::new(response))),
+            Err(()) => {
+                return Err(ErrorKind::TooManyRedirects(Box::new(
+                    self.finish(request, response),
+                )))
+            }
+        }
+
+        let rewrite = match response.status_code {
+            303 => request.method != HttpMethod::Head,
+            301 | 302 => request.method == HttpMethod::Post,
+            _ => false,
+        };
+
+        // a streamed body was consumed by this hop, so it has to start over for the next one
+        if !rewrite && !request.rewind_body() {
+            return Err(ErrorKind::RedirectBodyNotRewindable(Box::new(
+                self.finish(request, response),
+            )));
+        }
+
+        self.history.push(Redirect {
+            url: request.url.clone(),
+            status_code: response.status_code,
+        });
+
+        let mut next = request.share();
+
+        if rewrite {
+            next.method = HttpMethod::Get;
+            next.body = None;
+            next.remove_header("Content-Type");
+            next.remove_header("Content-Length");
+            next.remove_header("Transfer-Encoding");
+        }
+
+        if next.url.origin() != location.origin() {
+            next.unset_header("Authorization");
+            next.remove_header("Cookie");
+            next.cross_origin = true;
+        }
+
+        next.url = location;
+
+        Ok(Hop::Follow(Box::new(next)))
+    }
+
+    pub(crate) fn finish(&mut self, request: &Request, mut response: Response) -> Response {
+        response.redirects = std::mem::take(&mut self.history);
+        response.effective_url = Some(request.url.clone());
+        response
+    }
+}
+
+fn location(url: &Url, response: &Response) -> Option<Url> {
+    if !matches!(response.status_code, 301 | 302 | 303 | 307 | 308) {
+        return None;
+    }
+
+    let mut location = url.join(response.header("Location")?).ok()?;
+
+    if location.fragment().is_none() {
+        location.set_fragment(url.fragment());
+    }
+
+    Some(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Body;
+
+    fn redirect(status_code: u32, location: &str) -> Response {
+        Response {
+            status_code,
+            headers: [("Location", location)].into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn post(url: &str) -> Request {
+        let mut request = Request::new(Url::parse(url).unwrap());
+        request.set_method(HttpMethod::Post);
+        request.set_body("payload");
+        request.set_header("Content-Type", "text/plain");
+        request.set_header("Authorization", "Bearer token");
+        request
+    }
+
+    fn follows(redirects: &mut Redirects, request: &mut Request, response: Response) -> bool {
+        match redirects.follow(request, response) {
+            Ok(Hop::Follow(next)) => {
+                *request = *next;
+                true
+            }
+            Ok(Hop::Stop(_)) => false,
+            Err(_) => panic!("unexpected error"),
+        }
+    }
+
+    #[test]
+    fn test_method_rewriting() {
+        let mut redirects = Redirects::new(RedirectPolicy::Limited(5));
+
+        let mut request = post("http://a.test/form");
+        assert!(follows(
+            &mut redirects,
+            &mut request,
+            redirect(307, "/kept")
+        ));
+        assert_eq!(request.method, HttpMethod::Post);
+        assert!(request.body.is_some());
+        assert_eq!(request.url.as_str(), "http://a.test/kept");
+
+        assert!(follows(
+            &mut redirects,
+            &mut request,
+            redirect(303, "/see-other")
+        ));
+        assert_eq!(request.method, HttpMethod::Get);
+        assert!(request.body.is_none());
+        assert!(!request.headers.contains("Content-Type"));
+        assert_eq!(request.headers.get("Authorization"), Some("Bearer token"));
+
+        let response = redirects.finish(&request, Response::default());
+        assert_eq!(
+            response
+                .redirects
+                .iter()
+                .map(|hop| (hop.url.as_str(), hop.status_code))
+                .collect::<Vec<_>>(),
+            vec![("http://a.test/form", 307), ("http://a.test/kept", 303)]
+        );
+        assert_eq!(
+            response.effective_url.unwrap().as_str(),
+            "http://a.test/see-other"
+        );
+    }
+
+    #[test]
+    fn test_cross_origin_strips_authorization() {
+        let mut redirects = Redirects::new(RedirectPolicy::Limited(5));
+
+        let request = post("http://a.test/form");
+        let next = match redirects.follow(&request, redirect(308, "https://b.test/form")) {
+            Ok(Hop::Follow(next)) => next,
+            _ => panic!("redirect wasn't followed"),
+        };
+
+        assert_eq!(next.method, HttpMethod::Post);
+        assert!(next.headers.get("Authorization").is_none());
+        assert!(next.headers.is_unset("Authorization"));
+
+        assert!(next.cross_origin);
+
+        assert_eq!(request.url.as_str(), "http://a.test/form");
+        assert_eq!(request.headers.get("Authorization"), Some("Bearer token"));
+        assert!(!request.cross_origin);
+    }
+
+    #[test]
+    fn test_consumed_body_fails_the_redirect() {
+        for (method, status_code) in [(HttpMethod::Put, 302), (HttpMethod::Post, 307)] {
+            let mut request = post("http://a.test/upload");
+            request.set_method(method);
+            request.set_body(Body::from_reader(&b"payload"[..], Some(7)));
+            futures_executor::block_on(request.read_body()).unwrap();
+
+            let mut redirects = Redirects::new(RedirectPolicy::Limited(5));
+            assert!(matches!(
+                redirects.follow(&request, redirect(status_code, "/moved")),
+                Err(ErrorKind::RedirectBodyNotRewindable(response))
+                    if response.status_code == status_code
+            ));
+        }
+
+        // a POST turned into a GET leaves the body behind
+        let mut request = post("http://a.test/upload");
+        request.set_body(Body::from_reader(&b"payload"[..], Some(7)));
+        futures_executor::block_on(request.read_body()).unwrap();
+
+        let mut redirects = Redirects::new(RedirectPolicy::Limited(5));
+        assert!(follows(
+            &mut redirects,
+            &mut request,
+            redirect(302, "/moved")
+        ));
+        assert!(request.body.is_none());
+    }
+
+    #[test]
+    fn test_policies() {
+        let mut request = post("http://a.test/");
+        let mut redirects = Redirects::new(RedirectPolicy::None);
+        assert!(!follows(
+            &mut redirects,
+            &mut request,
+            redirect(302, "/next")
+        ));
+
+        let mut redirects = Redirects::new(RedirectPolicy::SameOrigin);
+        assert!(!follows(
+            &mut redirects,
+            &mut request,
+            redirect(302, "http://b.test/")
+        ));
+
+        let only_https =
+            RedirectPolicy::custom(|attempt: &RedirectAttempt| attempt.to.scheme() == "https");
+        let mut redirects = Redirects::new(only_https);
+        assert!(!follows(
+            &mut redirects,
+            &mut request,
+            redirect(302, "http://a.test/plain")
+        ));
+        assert!(follows(
+            &mut redirects,
+            &mut request,
+            redirect(302, "https://a.test/secure")
+        ));
+
+        let mut redirects = Redirects::new(RedirectPolicy::Limited(1));
+        let mut request = post("http://a.test/");
+        assert!(follows(&mut redirects, &mut request, redirect(301, "/one")));
+        assert!(matches!(
+            redirects.follow(&request, redirect(301, "/two")),
+            Err(ErrorKind::TooManyRedirects(response)) if response.redirects.len() == 1
+        ));
+    }
+}
@@ -1,17 +1,34 @@
 use std::borrow::Borrow;
 use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use std::time::Duration;
 
 use url::Url;
 
+use std::sync::Arc;
+
+use crate::headers::is_token;
 use crate::hexdump::hexdump;
+use crate::progress::ProgressFn;
+use crate::{
+    Body, Headers, InvalidMethod, Multipart, Progress, RedirectPolicy, RetryPolicy, Timeouts,
+};
 
 pub struct Request {
     pub url: Url,
     pub method: HttpMethod,
-    pub headers: Option<Vec<(String, String)>>,
-    pub form: Option<Vec<(String, String)>>,
-    pub body: Option<Vec<u8>>,
+    pub headers: Headers,
+    pub form: Option<Multipart>,
+    pub body: Option<Body>,
     pub retry_count: Option<u8>,
+    pub retry_policy: Option<Arc<dyn RetryPolicy>>,
+    pub timeouts: Timeouts,
+    pub progress: Option<Arc<ProgressFn>>,
+    pub redirect_policy: Option<RedirectPolicy>,
+    // set once a redirect leaves the original origin
+    pub(crate) cross_origin: bool,
 }
 
 impl fmt::Debug for Request {
@@ -23,45 +40,119 @@ impl fmt::Debug for Request {
             .field("method", &self.method)
             .field("url", &self.url.to_string());
 
-        if let Some(body) = self.body.as_ref() {
-            if alternate {
+        match (
+            self.body.as_ref(),
+            self.body.as_ref().and_then(Body::as_bytes),
+        ) {
+            (Some(_), Some(bytes)) if alternate => {
                 debug.finish()?;
 
                 writeln!(f)?;
-                hexdump(body, f)
-            } else {
-                debug.field("body", &format!("{} bytes", body.len()));
+                hexdump(bytes, f)
+            }
+            (Some(body), _) => {
+                debug.field("body", &format!("{:?}", body));
                 debug.finish()
             }
-        } else {
-            debug.finish()
+            (None, _) => debug.finish(),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum HttpMethod {
+    #[default]
     Get,
     Post,
     Put,
     Delete,
+    Patch,
+    Head,
+    Options,
+    Propfind,
+    Mkcol,
+    Custom(String),
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Propfind => "PROPFIND",
+            HttpMethod::Mkcol => "MKCOL",
+            HttpMethod::Custom(method) => method,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let method = self.as_str();
+        !method.is_empty() && method.bytes().all(is_token)
+    }
+
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            HttpMethod::Get
+                | HttpMethod::Put
+                | HttpMethod::Delete
+                | HttpMethod::Head
+                | HttpMethod::Options
+                | HttpMethod::Propfind
+        )
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = InvalidMethod;
+
+    fn from_str(method: &str) -> Result<HttpMethod, InvalidMethod> {
+        let method = match method {
+            "GET" => HttpMethod::Get,
+            "POST" => HttpMethod::Post,
+            "PUT" => HttpMethod::Put,
+            "DELETE" => HttpMethod::Delete,
+            "PATCH" => HttpMethod::Patch,
+            "HEAD" => HttpMethod::Head,
+            "OPTIONS" => HttpMethod::Options,
+            "PROPFIND" => HttpMethod::Propfind,
+            "MKCOL" => HttpMethod::Mkcol,
+            method => HttpMethod::Custom(method.to_string()),
+        };
+
+        if method.is_valid() {
+            Ok(method)
+        } else {
+            Err(InvalidMethod(method.to_string()))
+        }
+    }
 }
 
-impl Default for HttpMethod {
-    fn default() -> HttpMethod {
-        HttpMethod::Get
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
 impl Request {
-    pub fn new<'r>(url: Url) -> Request {
+    pub fn new(url: Url) -> Request {
         Request {
             url,
             method: HttpMethod::default(),
             form: None,
-            headers: None,
+            headers: Headers::new(),
             body: None,
             retry_count: None,
+            retry_policy: None,
+            timeouts: Timeouts::default(),
+            progress: None,
+            redirect_policy: None,
+            cross_origin: false,
         }
     }
 }
@@ -76,14 +167,23 @@ impl Request {
         H: ToString,
         V: ToString,
     {
-        if let None = self.headers {
-            self.headers = Some(vec![]);
-        }
+        self.headers.append(header, value)
+    }
+
+    pub fn set_header<H, V>(&mut self, header: H, value: V)
+    where
+        H: ToString,
+        V: ToString,
+    {
+        self.headers.insert(header, value)
+    }
+
+    pub fn remove_header(&mut self, header: &str) {
+        self.headers.remove(header)
+    }
 
-        self.headers
-            .as_mut()
-            .unwrap()
-            .push((header.to_string(), value.to_string()));
+    pub fn unset_header<H: ToString>(&mut self, header: H) {
+        self.headers.unset(header)
     }
 
     pub fn set_form<I, K, V>(&mut self, form_iter: I)
@@ -93,16 +193,20 @@ impl Request {
         K: ToString,
         V: ToString,
     {
-        let mut form = vec![];
+        let mut form = Multipart::new();
 
         for pair in form_iter.into_iter() {
-            let &(ref k, ref v) = pair.borrow();
-            form.push((k.to_string(), v.to_string()));
+            let (k, v) = pair.borrow();
+            form = form.text(k.to_string(), v.to_string());
         }
 
         self.form = Some(form)
     }
 
+    pub fn set_multipart(&mut self, multipart: Multipart) {
+        self.form = Some(multipart)
+    }
+
     pub fn set_urlencoded_params<I, K, V>(&mut self, params: I)
     where
         I: IntoIterator,
@@ -113,22 +217,111 @@ impl Request {
         let mut serializer = url::form_urlencoded::Serializer::new(String::default());
 
         for pair in params.into_iter() {
-            let &(ref k, ref v) = pair.borrow();
+            let (k, v) = pair.borrow();
             serializer.append_pair(k.as_ref(), v.as_ref());
         }
 
-        self.body = Some(serializer.finish().into_bytes())
+        self.body = Some(serializer.finish().into())
+    }
+
+    pub fn set_body<B: Into<Body>>(&mut self, body: B) {
+        self.body = Some(body.into())
     }
 
     pub fn set_json_body<J: serde::Serialize>(&mut self, json: &J) {
         let body = serde_json::to_vec(&json).expect("valid json argument");
-        self.body = Some(body);
-        self.add_header("Content-Type", "application/json")
+        self.body = Some(body.into());
+        self.set_header("Content-Type", "application/json")
+    }
+
+    pub(crate) async fn read_body(&self) -> io::Result<Option<Vec<u8>>> {
+        let body = self
+            .form
+            .as_ref()
+            .map(Multipart::body)
+            .or(self.body.as_ref());
+
+        match body {
+            Some(body) => body.read_to_end().await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) async fn try_copy(&self) -> io::Result<Request> {
+        Ok(Request {
+            body: self.read_body().await?.map(Body::from),
+            retry_count: None,
+            retry_policy: None,
+            ..self.share()
+        })
+    }
+
+    // the copy reads from the same body, so only one of them can be sent at a time
+    pub(crate) fn share(&self) -> Request {
+        let mut headers = self.headers.clone();
+
+        if let Some(form) = &self.form {
+            if !headers.contains("Content-Type") {
+                headers.insert("Content-Type", form.content_type());
+            }
+        }
+
+        let body = self
+            .form
+            .as_ref()
+            .map(Multipart::body)
+            .or(self.body.as_ref());
+
+        Request {
+            url: self.url.clone(),
+            method: self.method.clone(),
+            headers,
+            form: None,
+            body: body.map(Body::share),
+            retry_count: self.retry_count,
+            retry_policy: self.retry_policy.clone(),
+            timeouts: self.timeouts,
+            progress: self.progress.clone(),
+            redirect_policy: self.redirect_policy.clone(),
+            cross_origin: self.cross_origin,
+        }
+    }
+
+    pub(crate) fn rewind_body(&self) -> bool {
+        self.body.as_ref().is_none_or(Body::rewind)
+            && self.form.as_ref().is_none_or(Multipart::rewind)
     }
 
     pub fn set_retry_count(&mut self, retry_count: u8) {
         self.retry_count = Some(retry_count)
     }
+
+    pub fn set_retry_policy<P: RetryPolicy + 'static>(&mut self, policy: P) {
+        self.retry_policy = Some(Arc::new(policy))
+    }
+
+    pub fn set_redirect_policy(&mut self, policy: RedirectPolicy) {
+        self.redirect_policy = Some(policy)
+    }
+
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.timeouts.connect = Some(timeout)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeouts.total = Some(timeout)
+    }
+
+    pub fn set_low_speed_timeout(&mut self, bytes_per_second: u32, time: Duration) {
+        self.timeouts.low_speed = Some((bytes_per_second, time))
+    }
+
+    pub fn set_progress<F>(&mut self, progress: F)
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(progress))
+    }
 }
 
 #[cfg(test)]
@@ -145,4 +338,32 @@ mod tests {
             r#"Request { method: Post, url: "https://example.com/" }"#
         );
     }
+
+    #[test]
+    fn test_method() {
+        assert_eq!(HttpMethod::Patch.as_str(), "PATCH");
+        assert_eq!(
+            HttpMethod::Custom("REPORT".to_string()).to_string(),
+            "REPORT"
+        );
+
+        assert_eq!("PATCH".parse(), Ok(HttpMethod::Patch));
+        assert_eq!(
+            "REPORT".parse(),
+            Ok(HttpMethod::Custom("REPORT".to_string()))
+        );
+
+        assert!(HttpMethod::Custom("M-SEARCH".to_string()).is_valid());
+        assert!(!HttpMethod::Custom(String::new()).is_valid());
+        assert!(!HttpMethod::Custom("GET /other HTTP/1.1\r\nX-Injected: 1".to_string()).is_valid());
+        assert_eq!(
+            "GET /".parse::<HttpMethod>(),
+            Err(InvalidMethod("GET /".to_string()))
+        );
+        assert!("LOCK\r\n".parse::<HttpMethod>().is_err());
+
+        assert!(HttpMethod::Head.is_idempotent());
+        assert!(!HttpMethod::Patch.is_idempotent());
+        assert!(!HttpMethod::Custom("LOCK".to_string()).is_idempotent());
+    }
 }
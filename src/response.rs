@@ -1,11 +1,121 @@
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use url::Url;
 
 use crate::hexdump::hexdump;
+use crate::{Headers, Redirect};
 
+#[derive(Clone, Default)]
 pub struct Response {
     pub status_code: u32,
+    pub version: String,
+    pub reason: String,
     pub body: Vec<u8>,
-    pub headers: Vec<String>,
+    pub headers: Headers,
+    pub previous: Vec<ResponseHead>,
+    pub redirects: Vec<Redirect>,
+    pub effective_url: Option<Url>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseHead {
+    pub status_code: u32,
+    pub version: String,
+    pub reason: String,
+    pub headers: Headers,
+}
+
+impl Response {
+    pub(crate) fn from_raw(status_code: u32, lines: &[String], body: Vec<u8>) -> Response {
+        Response {
+            status_code,
+            body,
+            ..Response::from_head(lines)
+        }
+    }
+
+    // header values may carry obs-text, which isn't valid UTF-8
+    pub(crate) fn head_line(raw: &[u8]) -> String {
+        String::from_utf8_lossy(raw).trim_end().to_string()
+    }
+
+    pub(crate) fn from_head(lines: &[String]) -> Response {
+        let mut heads = parse_heads(lines);
+        let head = heads.pop().unwrap_or_default();
+
+        Response {
+            status_code: head.status_code,
+            version: head.version,
+            reason: head.reason,
+            body: vec![],
+            headers: head.headers,
+            previous: heads,
+            redirects: vec![],
+            effective_url: None,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        if let Some(value) = self.header("Retry-After") {
+            return match value.parse::<u64>() {
+                Ok(seconds) => Some(Duration::from_secs(seconds)),
+                Err(_) => httpdate::parse_http_date(value).ok().map(duration_until),
+            };
+        }
+
+        let reset = self
+            .header("RateLimit-Reset")
+            .or_else(|| self.header("X-RateLimit-Reset"))?
+            .parse::<u64>()
+            .ok()?;
+
+        // large values are unix timestamps rather than a number of seconds
+        if reset > 1_000_000_000 {
+            Some(duration_until(UNIX_EPOCH + Duration::from_secs(reset)))
+        } else {
+            Some(Duration::from_secs(reset))
+        }
+    }
+}
+
+fn duration_until(time: SystemTime) -> Duration {
+    time.duration_since(SystemTime::now()).unwrap_or_default()
+}
+
+fn parse_heads(lines: &[String]) -> Vec<ResponseHead> {
+    let mut heads: Vec<ResponseHead> = vec![];
+
+    for line in lines {
+        if line.starts_with("HTTP/") {
+            let mut parts = line.splitn(3, ' ');
+
+            heads.push(ResponseHead {
+                version: parts.next().unwrap_or_default().to_string(),
+                status_code: parts.next().and_then(|code| code.parse().ok()).unwrap_or(0),
+                reason: parts.next().unwrap_or_default().trim().to_string(),
+                headers: Headers::new(),
+            });
+
+            continue;
+        }
+
+        let Some(head) = heads.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with([' ', '\t']) {
+            head.headers.extend_last(line.trim());
+        } else if let Some((name, value)) = line.split_once(':') {
+            head.headers.append(name.trim(), value.trim());
+        }
+    }
+
+    heads
 }
 
 impl fmt::Debug for Response {
@@ -47,22 +157,93 @@ mod tests {
         let res = Response {
             status_code: 404,
             body: Vec::from("Not Found!!!".as_bytes()),
-            headers: vec!["X-Custom: None".to_string()],
+            headers: [("X-Custom", "None")].into_iter().collect(),
+            ..Default::default()
         };
 
         assert_eq!(
             format!("{:?}", res),
-            r#"Response { status_code: 404, headers: ["X-Custom: None"] }
+            r#"Response { status_code: 404, headers: {"X-Custom": "None"} }
 00000000  4e 6f 74 20 46 6f 75 6e  64 21 21 21              |Not.Found!!!|"#
         );
     }
 
+    fn lines(raw: &str) -> Vec<String> {
+        raw.split("\r\n").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_from_raw() {
+        let res = Response::from_raw(
+            200,
+            &lines("HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\nX-Folded: a\r\n b\r\n"),
+            vec![],
+        );
+
+        assert_eq!(res.version, "HTTP/1.1");
+        assert_eq!(res.reason, "OK");
+        assert_eq!(res.header("Content-Type"), Some("text/plain"));
+        assert_eq!(res.header("X-Folded"), Some("a b"));
+        assert_eq!(res.header("Location"), None);
+        assert!(res.previous.is_empty());
+    }
+
+    #[test]
+    fn test_from_raw_multiple_blocks() {
+        let res = Response::from_raw(
+            200,
+            &lines(
+                "HTTP/1.1 100 Continue\r\n\r\n\
+                 HTTP/1.1 302 Found\r\nLocation: /next\r\n\r\n\
+                 HTTP/2 200\r\nX-Final: yes\r\n",
+            ),
+            vec![],
+        );
+
+        assert_eq!(res.version, "HTTP/2");
+        assert_eq!(res.reason, "");
+        assert_eq!(res.header("X-Final"), Some("yes"));
+        assert_eq!(res.header("Location"), None);
+
+        assert_eq!(res.previous.len(), 2);
+        assert_eq!(res.previous[0].status_code, 100);
+        assert_eq!(res.previous[0].reason, "Continue");
+        assert_eq!(res.previous[1].status_code, 302);
+        assert_eq!(res.previous[1].headers.get("location"), Some("/next"));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut res = Response {
+            status_code: 429,
+            headers: [("Retry-After", "120")].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(res.retry_after(), Some(Duration::from_secs(120)));
+
+        res.headers = [("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT")]
+            .into_iter()
+            .collect();
+        assert_eq!(res.retry_after(), Some(Duration::ZERO));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        res.headers = [("Retry-After", date)].into_iter().collect();
+        assert!(res.retry_after().unwrap() > Duration::from_secs(25));
+
+        res.headers = [("X-RateLimit-Reset", "15")].into_iter().collect();
+        assert_eq!(res.retry_after(), Some(Duration::from_secs(15)));
+
+        res.headers = [("Retry-After", "soon")].into_iter().collect();
+        assert_eq!(res.retry_after(), None);
+    }
+
     #[test]
     fn test_display() {
         let res = Response {
             status_code: 404,
             body: Vec::from("Not Found!!!".as_bytes()),
-            headers: vec!["X-Custom: None".to_string()],
+            headers: [("X-Custom", "None")].into_iter().collect(),
+            ..Default::default()
         };
 
         assert_eq!(
@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use crate::{random, ErrorKind, Request, Response};
+
+pub struct RetryContext<'a> {
+    pub request: &'a Request,
+    pub attempt: u32,
+    pub elapsed: Duration,
+    pub outcome: Result<&'a Response, &'a ErrorKind>,
+}
+
+pub trait RetryPolicy: Send + Sync {
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration>;
+}
+
+impl<T: Fn(&RetryContext) -> Option<Duration> + Send + Sync> RetryPolicy for T {
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration> {
+        self(context)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+    pub max_elapsed: Option<Duration>,
+    pub jitter: bool,
+    pub statuses: Vec<u32>,
+    pub retry_non_idempotent: bool,
+    pub respect_retry_after: bool,
+    pub max_retry_after: Duration,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> ExponentialBackoff {
+        ExponentialBackoff {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(10),
+            max_elapsed: None,
+            jitter: true,
+            statuses: vec![429, 502, 503, 504],
+            retry_non_idempotent: false,
+            respect_retry_after: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ExponentialBackoff {
+    pub fn new(max_retries: u32) -> ExponentialBackoff {
+        ExponentialBackoff {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    fn should_retry(&self, context: &RetryContext) -> bool {
+        if context.attempt > self.max_retries {
+            return false;
+        }
+
+        if !self.retry_non_idempotent && !context.request.method.is_idempotent() {
+            return false;
+        }
+
+        match context.outcome {
+            Ok(response) => self.statuses.contains(&response.status_code),
+            Err(ErrorKind::CurlError(_)) | Err(ErrorKind::Timeout { .. }) => true,
+            Err(_) => false,
+        }
+    }
+
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let delay = delay.min(self.max_delay.as_secs_f64());
+
+        if self.jitter {
+            Duration::from_secs_f64(delay * (0.5 + random_fraction() * 0.5))
+        } else {
+            Duration::from_secs_f64(delay)
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration> {
+        if !self.should_retry(context) {
+            return None;
+        }
+
+        let retry_after = match context.outcome {
+            Ok(response) if self.respect_retry_after => response.retry_after(),
+            _ => None,
+        };
+
+        let delay = match retry_after {
+            Some(retry_after) => retry_after.min(self.max_retry_after),
+            None => self.delay_for_attempt(context.attempt),
+        };
+
+        match self.max_elapsed {
+            Some(max_elapsed) if context.elapsed + delay > max_elapsed => None,
+            _ => Some(delay),
+        }
+    }
+}
+
+pub(crate) struct RetryCount(pub u8);
+
+impl RetryPolicy for RetryCount {
+    fn retry_delay(&self, context: &RetryContext) -> Option<Duration> {
+        match context.outcome {
+            Err(ErrorKind::CurlError(_)) | Err(ErrorKind::Timeout { .. })
+                if context.attempt < self.0 as u32 =>
+            {
+                Some(Duration::from_millis(delay_for_attempt(context.attempt)))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn delay_for_attempt(attempt: u32) -> u64 {
+    let delay = (attempt as f64) * 0.5 + 1_f64;
+    let delay = delay.exp() * 100_f64;
+    delay as u64
+}
+
+fn random_fraction() -> f64 {
+    (random() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    fn context<'a>(
+        request: &'a Request,
+        attempt: u32,
+        outcome: Result<&'a Response, &'a ErrorKind>,
+    ) -> RetryContext<'a> {
+        RetryContext {
+            request,
+            attempt,
+            elapsed: Duration::ZERO,
+            outcome,
+        }
+    }
+
+    fn response(status_code: u32) -> Response {
+        Response {
+            status_code,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_delay() {
+        assert_eq!(delay_for_attempt(1), 448);
+        assert_eq!(delay_for_attempt(2), 738);
+        assert_eq!(delay_for_attempt(3), 1218);
+    }
+
+    #[test]
+    fn test_backoff_statuses() {
+        let policy = ExponentialBackoff {
+            jitter: false,
+            ..Default::default()
+        };
+        let request = Request::new(Url::parse("https://example.com").unwrap());
+
+        let unavailable = response(503);
+        assert_eq!(
+            policy.retry_delay(&context(&request, 1, Ok(&unavailable))),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay(&context(&request, 3, Ok(&unavailable))),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(
+            policy.retry_delay(&context(&request, 4, Ok(&unavailable))),
+            None
+        );
+
+        let not_found = response(404);
+        assert_eq!(
+            policy.retry_delay(&context(&request, 1, Ok(&not_found))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_backoff_skips_non_idempotent() {
+        let policy = ExponentialBackoff::default();
+
+        let mut request = Request::new(Url::parse("https://example.com").unwrap());
+        request.set_method(crate::HttpMethod::Post);
+
+        let unavailable = response(503);
+        assert_eq!(
+            policy.retry_delay(&context(&request, 1, Ok(&unavailable))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_backoff_limits() {
+        let policy = ExponentialBackoff {
+            max_retries: 10,
+            max_delay: Duration::from_millis(250),
+            max_elapsed: Some(Duration::from_secs(1)),
+            jitter: false,
+            ..Default::default()
+        };
+        let request = Request::new(Url::parse("https://example.com").unwrap());
+        let unavailable = response(503);
+
+        assert_eq!(
+            policy.retry_delay(&context(&request, 5, Ok(&unavailable))),
+            Some(Duration::from_millis(250))
+        );
+
+        let mut late = context(&request, 5, Ok(&unavailable));
+        late.elapsed = Duration::from_millis(900);
+        assert_eq!(policy.retry_delay(&late), None);
+    }
+
+    #[test]
+    fn test_backoff_retry_after() {
+        let policy = ExponentialBackoff {
+            max_retry_after: Duration::from_secs(30),
+            ..Default::default()
+        };
+        let request = Request::new(Url::parse("https://example.com").unwrap());
+
+        let mut limited = response(429);
+        limited.headers = [("Retry-After", "5")].into_iter().collect();
+        assert_eq!(
+            policy.retry_delay(&context(&request, 1, Ok(&limited))),
+            Some(Duration::from_secs(5))
+        );
+
+        limited.headers = [("Retry-After", "3600")].into_iter().collect();
+        assert_eq!(
+            policy.retry_delay(&context(&request, 1, Ok(&limited))),
+            Some(Duration::from_secs(30))
+        );
+
+        let ignoring = ExponentialBackoff {
+            respect_retry_after: false,
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            ignoring.retry_delay(&context(&request, 1, Ok(&limited))),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let policy = ExponentialBackoff::default();
+
+        for _ in 0..100 {
+            let delay = policy.delay_for_attempt(2);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
+}
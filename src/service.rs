@@ -0,0 +1,31 @@
+use std::task::{Context, Poll};
+
+use tower_service::Service;
+
+use crate::{BoxFuture, Error, HttpClient, Interceptor, Request, Response};
+
+impl<X: Interceptor + Clone + 'static> Service<Request> for HttpClient<X> {
+    type Response = Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Response, Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if self.reservation.is_held() {
+            return Poll::Ready(Ok(()));
+        }
+
+        self.driver.poll_reserve(cx).map(|slot| {
+            self.reservation.hold(slot);
+            Ok(())
+        })
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let client = self.clone();
+        if let Some(slot) = self.reservation.take() {
+            client.reservation.hold(slot);
+        }
+
+        Box::pin(async move { client.perform_request(request, |_, res| Ok(res)).await })
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use ::curl::easy::{Easy, WriteError};
+use bytes::Bytes;
+use futures_core::Stream;
+
+use crate::driver::{Resume, Transfer};
+use crate::middleware::{Attempt, Endpoint};
+use crate::{
+    transfer_error, BoxFuture, Error, ErrorKind, HttpClient, Interceptor, Request, Response,
+    Timeouts,
+};
+
+const MAX_BUFFERED: usize = 256 * 1024;
+
+pub struct StreamingResponse {
+    pub response: Response,
+    pub body: BodyStream,
+}
+
+pub struct BodyStream {
+    request: Option<Request>,
+    shared: Arc<Mutex<Shared>>,
+    resume: Arc<Resume>,
+    transfer: Option<Transfer>,
+    timeouts: Timeouts,
+}
+
+#[derive(Default)]
+struct Shared {
+    headers: Vec<String>,
+    chunks: VecDeque<Bytes>,
+    buffered: usize,
+    status: u32,
+    head_complete: bool,
+    tunnel: bool,
+    paused: bool,
+    waker: Option<Waker>,
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub async fn perform_streaming(
+        &self,
+        mut request: Request,
+    ) -> Result<StreamingResponse, Error> {
+        let endpoint = Streaming {
+            client: self,
+            body: Mutex::new(None),
+        };
+
+        let mut response = match self.dispatch_to(&mut request, &endpoint).await {
+            Ok(response) => response,
+            Err(kind) => {
+                return Err(Error {
+                    request: Box::new(request),
+                    kind,
+                })
+            }
+        };
+
+        // middleware that answers with a body of its own replaces the stream
+        let mut body = match endpoint.body.into_inner().unwrap() {
+            Some(body) if response.body.is_empty() => body,
+            _ => BodyStream::complete(std::mem::take(&mut response.body)),
+        };
+        body.request = Some(request);
+
+        Ok(StreamingResponse { response, body })
+    }
+}
+
+struct Streaming<'c, X: Interceptor> {
+    client: &'c HttpClient<X>,
+    body: Mutex<Option<BodyStream>>,
+}
+
+impl<X: Interceptor> Endpoint for Streaming<'_, X> {
+    fn send<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let mut streams = Streams {
+                client: self.client,
+                body: &self.body,
+                prepared: None,
+            };
+
+            self.client.send_hops(request, &mut streams).await
+        })
+    }
+}
+
+struct Streams<'c, X: Interceptor> {
+    client: &'c HttpClient<X>,
+    body: &'c Mutex<Option<BodyStream>>,
+    prepared: Option<Streamed>,
+}
+
+struct Streamed {
+    easy: Easy,
+    timeouts: Timeouts,
+    resume: Arc<Resume>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl<X: Interceptor> Streams<'_, X> {
+    fn prepare_easy(&self, request: &Request) -> Result<Streamed, ErrorKind> {
+        let resume = Arc::new(Resume::default());
+        let (mut easy, timeouts) = self.client.prepare_easy(request, &resume)?;
+
+        // curl hands the proxy's reply to CONNECT over like any other head
+        let tunnel = request.url.scheme() == "https"
+            && self
+                .client
+                .proxy_for(&request.url)
+                .is_some_and(|proxy| proxy.url.scheme().starts_with("http"));
+
+        let shared = Arc::new(Mutex::new(Shared {
+            tunnel,
+            ..Default::default()
+        }));
+        stream_response(&mut easy, &shared);
+
+        Ok(Streamed {
+            easy,
+            timeouts,
+            resume,
+            shared,
+        })
+    }
+}
+
+impl<X: Interceptor> Attempt for Streams<'_, X> {
+    fn prepare(&mut self, request: &mut Request) -> Result<(), ErrorKind> {
+        if self.client.transport.is_some() {
+            return self.client.apply_default_headers(request);
+        }
+
+        self.prepared = Some(self.prepare_easy(request)?);
+        Ok(())
+    }
+
+    fn perform<'a>(
+        &'a mut self,
+        request: &'a mut Request,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            // dropping the body of an earlier attempt or a followed redirect aborts its transfer
+            *self.body.lock().unwrap() = None;
+
+            if let Some(transport) = &self.client.transport {
+                return transport.send(request).await;
+            }
+
+            let Streamed {
+                easy,
+                timeouts,
+                resume,
+                shared,
+            } = match self.prepared.take() {
+                Some(prepared) => prepared,
+                None => self.prepare_easy(request)?,
+            };
+
+            let transfer =
+                self.client
+                    .driver
+                    .perform(easy, resume.clone(), self.client.reservation.take());
+
+            let mut body = BodyStream {
+                request: None,
+                shared,
+                resume,
+                transfer: Some(transfer),
+                timeouts,
+            };
+
+            poll_fn(|cx| body.poll_head(cx)).await?;
+
+            let headers = std::mem::take(&mut body.shared.lock().unwrap().headers);
+            *self.body.lock().unwrap() = Some(body);
+
+            Ok(Response::from_head(&headers))
+        })
+    }
+
+    fn pause(&self, delay: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(self.client.driver.delay(delay))
+    }
+}
+
+impl BodyStream {
+    fn complete(body: Vec<u8>) -> BodyStream {
+        let mut shared = Shared {
+            head_complete: true,
+            ..Default::default()
+        };
+
+        if !body.is_empty() {
+            shared.buffered = body.len();
+            shared.chunks.push_back(Bytes::from(body));
+        }
+
+        BodyStream {
+            request: None,
+            shared: Arc::new(Mutex::new(shared)),
+            resume: Arc::new(Resume::default()),
+            transfer: None,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    fn poll_head(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ErrorKind>> {
+        {
+            let mut shared = self.shared.lock().unwrap();
+            if shared.head_complete {
+                return Poll::Ready(Ok(()));
+            }
+
+            shared.waker = Some(cx.waker().clone());
+        }
+
+        match self.poll_completion(cx) {
+            Poll::Ready(Some(err)) => Poll::Ready(Err(err)),
+            Poll::Ready(None) => Poll::Ready(Ok(())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_completion(&mut self, cx: &mut Context<'_>) -> Poll<Option<ErrorKind>> {
+        let Some(transfer) = self.transfer.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match Pin::new(transfer).poll(cx) {
+            Poll::Ready(completion) => {
+                self.transfer = None;
+
+                match completion {
+                    (_, Ok(())) => Poll::Ready(None),
+                    (easy, Err(err)) => {
+                        Poll::Ready(Some(transfer_error(err, &easy, &self.timeouts)))
+                    }
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            {
+                let mut shared = self.shared.lock().unwrap();
+
+                if let Some(chunk) = shared.chunks.pop_front() {
+                    shared.buffered -= chunk.len();
+
+                    if shared.paused && shared.buffered <= MAX_BUFFERED / 2 {
+                        shared.paused = false;
+                        self.resume.request();
+                    }
+
+                    return Poll::Ready(Some(Ok(chunk)));
+                }
+
+                if self.transfer.is_none() {
+                    return Poll::Ready(None);
+                }
+
+                shared.waker = Some(cx.waker().clone());
+            }
+
+            match self.poll_completion(cx) {
+                Poll::Ready(Some(kind)) => {
+                    let request = self.request.take().unwrap();
+                    return Poll::Ready(Some(Err(Error {
+                        request: Box::new(request),
+                        kind,
+                    })));
+                }
+                Poll::Ready(None) => continue,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+fn stream_response(easy: &mut Easy, shared: &Arc<Mutex<Shared>>) {
+    let body = shared.clone();
+    easy.write_function(move |chunk| {
+        let mut shared = body.lock().unwrap();
+
+        if shared.buffered >= MAX_BUFFERED {
+            shared.paused = true;
+            return Err(WriteError::Pause);
+        }
+
+        shared.head_complete = true;
+        shared.buffered += chunk.len();
+        shared.chunks.push_back(Bytes::copy_from_slice(chunk));
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+
+        Ok(chunk.len())
+    })
+    .unwrap();
+
+    let headers = shared.clone();
+    easy.header_function(move |header| {
+        let line = Response::head_line(header);
+        let mut shared = headers.lock().unwrap();
+
+        if line.starts_with("HTTP/") {
+            shared.status = line
+                .split(' ')
+                .nth(1)
+                .and_then(|code| code.parse().ok())
+                .unwrap_or(0);
+        } else if line.is_empty() && !(100..200).contains(&shared.status) {
+            if shared.tunnel {
+                shared.tunnel = false;
+            } else if !shared.head_complete {
+                shared.head_complete = true;
+
+                if let Some(waker) = shared.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+
+        shared.headers.push(line);
+        true
+    })
+    .unwrap();
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use curl::easy::Easy;
+
+use crate::error::TimeoutPhase;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Timeouts {
+    pub connect: Option<Duration>,
+    pub total: Option<Duration>,
+    pub low_speed: Option<(u32, Duration)>,
+}
+
+impl Timeouts {
+    pub(crate) fn overridden_by(&self, overrides: &Timeouts) -> Timeouts {
+        Timeouts {
+            connect: overrides.connect.or(self.connect),
+            total: overrides.total.or(self.total),
+            low_speed: overrides.low_speed.or(self.low_speed),
+        }
+    }
+
+    pub(crate) fn apply(&self, easy: &mut Easy) {
+        if let Some(connect) = self.connect {
+            easy.connect_timeout(connect).unwrap();
+        }
+
+        if let Some(total) = self.total {
+            easy.timeout(total).unwrap();
+        }
+
+        if let Some((limit, time)) = self.low_speed {
+            easy.low_speed_limit(limit).unwrap();
+            easy.low_speed_time(time).unwrap();
+        }
+    }
+
+    pub(crate) fn phase(&self, easy: &Easy) -> TimeoutPhase {
+        // reused connections report a zero connect time, but have sent a request
+        let connected = easy.connect_time().is_ok_and(|time| !time.is_zero())
+            || easy.request_size().is_ok_and(|size| size > 0);
+
+        if !connected {
+            return TimeoutPhase::Connect;
+        }
+
+        match (self.total, self.low_speed) {
+            (Some(total), Some(_)) if easy.total_time().is_ok_and(|time| time < total) => {
+                TimeoutPhase::LowSpeed
+            }
+            (None, Some(_)) => TimeoutPhase::LowSpeed,
+            _ => TimeoutPhase::Total,
+        }
+    }
+}
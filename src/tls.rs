@@ -0,0 +1,327 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use curl::easy::{Easy, SslVersion};
+
+use crate::ErrorKind;
+
+#[derive(Clone, PartialEq)]
+pub struct Certificate {
+    pem: Vec<u8>,
+}
+
+impl Certificate {
+    pub fn from_pem<P: Into<Vec<u8>>>(pem: P) -> Certificate {
+        Certificate { pem: pem.into() }
+    }
+
+    pub fn from_der(der: &[u8]) -> Certificate {
+        Certificate {
+            pem: der_to_pem("CERTIFICATE", der),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Certificate> {
+        let contents = fs::read(path)?;
+
+        if is_pem(&contents) {
+            Ok(Certificate::from_pem(contents))
+        } else {
+            Ok(Certificate::from_der(&contents))
+        }
+    }
+}
+
+impl fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Certificate({} bytes)", self.pem.len())
+    }
+}
+
+#[derive(Clone)]
+pub struct Identity {
+    cert: Vec<u8>,
+    cert_type: &'static str,
+    key: Option<Vec<u8>>,
+    password: Option<String>,
+}
+
+impl Identity {
+    pub fn from_pem<C, K>(cert: C, key: K) -> Identity
+    where
+        C: Into<Vec<u8>>,
+        K: Into<Vec<u8>>,
+    {
+        Identity {
+            cert: cert.into(),
+            cert_type: "PEM",
+            key: Some(key.into()),
+            password: None,
+        }
+    }
+
+    pub fn from_pem_files<C, K>(cert: C, key: K) -> io::Result<Identity>
+    where
+        C: AsRef<Path>,
+        K: AsRef<Path>,
+    {
+        Ok(Identity::from_pem(fs::read(cert)?, fs::read(key)?))
+    }
+
+    pub fn from_pkcs12<D: Into<Vec<u8>>>(der: D, password: &str) -> Identity {
+        Identity {
+            cert: der.into(),
+            cert_type: "P12",
+            key: None,
+            password: Some(password.to_string()),
+        }
+    }
+
+    pub fn from_pkcs12_file<P: AsRef<Path>>(path: P, password: &str) -> io::Result<Identity> {
+        Ok(Identity::from_pkcs12(fs::read(path)?, password))
+    }
+
+    pub fn with_key_password(mut self, password: &str) -> Identity {
+        self.password = Some(password.to_string());
+        self
+    }
+}
+
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Identity")
+            .field("type", &self.cert_type)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsVersion {
+    Tls10,
+    Tls11,
+    Tls12,
+    Tls13,
+}
+
+impl From<TlsVersion> for SslVersion {
+    fn from(version: TlsVersion) -> SslVersion {
+        match version {
+            TlsVersion::Tls10 => SslVersion::Tlsv10,
+            TlsVersion::Tls11 => SslVersion::Tlsv11,
+            TlsVersion::Tls12 => SslVersion::Tlsv12,
+            TlsVersion::Tls13 => SslVersion::Tlsv13,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    pub root_certificates: Vec<Certificate>,
+    pub identity: Option<Identity>,
+    pub min_version: Option<TlsVersion>,
+    // in curl's "sha256//<base64 of the SubjectPublicKeyInfo digest>" form
+    pub pinned_public_keys: Vec<String>,
+}
+
+impl TlsConfig {
+    pub fn new() -> TlsConfig {
+        TlsConfig::default()
+    }
+
+    pub fn add_root_certificate(&mut self, certificate: Certificate) {
+        self.root_certificates.push(certificate)
+    }
+
+    pub fn set_identity(&mut self, identity: Identity) {
+        self.identity = Some(identity)
+    }
+
+    pub fn set_min_version(&mut self, version: TlsVersion) {
+        self.min_version = Some(version)
+    }
+
+    pub fn add_pinned_public_key<P: ToString>(&mut self, pin: P) {
+        self.pinned_public_keys.push(pin.to_string())
+    }
+
+    // an in-memory bundle replaces the default one, so carry it over
+    fn bundle(&self) -> io::Result<Vec<u8>> {
+        let mut bundle = read_bundle(default_bundle())?;
+
+        for certificate in &self.root_certificates {
+            bundle.push(b'\n');
+            bundle.extend_from_slice(&certificate.pem);
+        }
+
+        Ok(bundle)
+    }
+
+    fn configure(&self, easy: &mut Easy) -> Result<(), curl::Error> {
+        if let Some(identity) = &self.identity {
+            easy.ssl_cert_blob(&identity.cert)?;
+            easy.ssl_cert_type(identity.cert_type)?;
+
+            if let Some(key) = &identity.key {
+                easy.ssl_key_blob(key)?;
+                easy.ssl_key_type("PEM")?;
+            }
+
+            if let Some(password) = &identity.password {
+                easy.key_password(password)?;
+            }
+        }
+
+        if let Some(version) = self.min_version {
+            easy.ssl_min_max_version(version.into(), SslVersion::Default)?;
+        }
+
+        if !self.pinned_public_keys.is_empty() {
+            easy.pinned_public_key(&self.pinned_public_keys.join(";"))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct Tls {
+    config: TlsConfig,
+    // built on the first request that needs it, and reused by every request after
+    bundle: OnceLock<io::Result<Vec<u8>>>,
+}
+
+impl Tls {
+    pub(crate) fn new(config: TlsConfig) -> Tls {
+        Tls {
+            config,
+            bundle: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn apply(&self, easy: &mut Easy) -> Result<(), ErrorKind> {
+        if !self.config.root_certificates.is_empty() {
+            match self.bundle.get_or_init(|| self.config.bundle()) {
+                Ok(bundle) => easy.ssl_cainfo_blob(bundle).map_err(ErrorKind::CurlError)?,
+                Err(err) => return Err(ErrorKind::Io(io::Error::new(err.kind(), err.to_string()))),
+            }
+        }
+
+        self.config.configure(easy).map_err(ErrorKind::CurlError)
+    }
+}
+
+// the file curl verifies against: SSL_CERT_FILE, the one it was built with, or for
+// static builds, the one openssl-probe hands to every handle
+fn default_bundle() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("SSL_CERT_FILE") {
+        return Some(PathBuf::from(path));
+    }
+
+    if let Some(path) = curl::Version::get().cainfo() {
+        return Some(PathBuf::from(path));
+    }
+
+    #[cfg(unix)]
+    return openssl_probe::probe().cert_file;
+
+    #[cfg(not(unix))]
+    None
+}
+
+fn read_bundle(path: Option<PathBuf>) -> io::Result<Vec<u8>> {
+    let Some(path) = path else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no default CA bundle to add the root certificates to",
+        ));
+    };
+
+    fs::read(&path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("failed to read CA bundle {}: {}", path.display(), err),
+        )
+    })
+}
+
+fn is_pem(contents: &[u8]) -> bool {
+    contents
+        .windows(b"-----BEGIN".len())
+        .any(|window| window == b"-----BEGIN")
+}
+
+fn der_to_pem(label: &str, der: &[u8]) -> Vec<u8> {
+    let encoded = base64(der);
+
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+
+    pem.into_bytes()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_der_to_pem() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+
+        let pem = der_to_pem("CERTIFICATE", &[0xff; 60]);
+        let pem = String::from_utf8(pem).unwrap();
+        let lines: Vec<&str> = pem.lines().collect();
+
+        assert_eq!(lines[0], "-----BEGIN CERTIFICATE-----");
+        assert_eq!(lines[1].len(), 64);
+        assert_eq!(lines[2], "/".repeat(16));
+        assert_eq!(lines[3], "-----END CERTIFICATE-----");
+        assert!(is_pem(pem.as_bytes()));
+        assert!(!is_pem(&[0x30, 0x82, 0x01, 0x0a]));
+    }
+
+    #[test]
+    fn test_missing_bundle() {
+        let err = read_bundle(None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let err = read_bundle(Some(PathBuf::from("/nonexistent/ca.pem"))).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("/nonexistent/ca.pem"));
+    }
+}
@@ -0,0 +1,145 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use ::curl::easy::Easy;
+
+use crate::driver::{Driver, Resume, Slot};
+use crate::{
+    check_request, transfer_error, ErrorKind, HttpClient, Interceptor, Request, Response, Timeouts,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub trait Transport: Send + Sync {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, ErrorKind>>;
+}
+
+impl<X: Interceptor> Transport for HttpClient<X> {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let request = request.try_copy().await.map_err(ErrorKind::Io)?;
+
+            self.perform_request(request, |_, response| Ok(response))
+                .await
+                .map_err(|err| err.kind)
+        })
+    }
+}
+
+pub(crate) enum Exchange<'a> {
+    Curl(CurlExchange),
+    Custom(&'a dyn Transport),
+}
+
+pub(crate) struct CurlExchange {
+    easy: Option<Easy>,
+    timeouts: Timeouts,
+    data: Arc<Mutex<ResponseData>>,
+    resume: Arc<Resume>,
+}
+
+impl<X: Interceptor> HttpClient<X> {
+    pub fn set_transport<T: Transport + 'static>(&mut self, transport: T) {
+        self.transport = Some(Arc::new(transport))
+    }
+
+    pub(crate) fn apply_default_headers(&self, request: &mut Request) -> Result<(), ErrorKind> {
+        check_request(request)?;
+
+        let headers = self.request_headers(request);
+        headers.validate().map_err(ErrorKind::InvalidHeader)?;
+        request.headers = headers;
+
+        Ok(())
+    }
+
+    pub(crate) fn exchange(&self, request: &mut Request) -> Result<Exchange<'_>, ErrorKind> {
+        if let Some(transport) = &self.transport {
+            self.apply_default_headers(request)?;
+            return Ok(Exchange::Custom(transport.as_ref()));
+        }
+
+        let resume = Arc::new(Resume::default());
+        let (mut easy, timeouts) = self.prepare_easy(request, &resume)?;
+
+        let data = Arc::new(Mutex::new(ResponseData::default()));
+        collect_response(&mut easy, &data);
+
+        Ok(Exchange::Curl(CurlExchange {
+            easy: Some(easy),
+            timeouts,
+            data,
+            resume,
+        }))
+    }
+}
+
+impl Exchange<'_> {
+    pub(crate) async fn perform(
+        &mut self,
+        driver: &Driver,
+        reserved: Option<Slot>,
+        request: &Request,
+    ) -> Result<Response, ErrorKind> {
+        match self {
+            Exchange::Curl(curl) => curl.perform(driver, reserved).await,
+            Exchange::Custom(transport) => transport.send(request).await,
+        }
+    }
+}
+
+impl CurlExchange {
+    async fn perform(
+        &mut self,
+        driver: &Driver,
+        reserved: Option<Slot>,
+    ) -> Result<Response, ErrorKind> {
+        *self.data.lock().unwrap() = ResponseData::default();
+
+        let easy = self.easy.take().unwrap();
+        let (easy, result) = driver.perform(easy, self.resume.clone(), reserved).await;
+
+        let outcome = match result {
+            Ok(()) => {
+                let ResponseData { body, headers } =
+                    std::mem::take(&mut *self.data.lock().unwrap());
+                Ok(Response::from_raw(
+                    easy.response_code().unwrap(),
+                    &headers,
+                    body,
+                ))
+            }
+            Err(err) => Err(transfer_error(err, &easy, &self.timeouts)),
+        };
+
+        self.easy = Some(easy);
+        outcome
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ResponseData {
+    pub(crate) body: Vec<u8>,
+    pub(crate) headers: Vec<String>,
+}
+
+pub(crate) fn collect_response(easy: &mut Easy, data: &Arc<Mutex<ResponseData>>) {
+    let body = data.clone();
+    easy.write_function(move |chunk| {
+        body.lock().unwrap().body.extend_from_slice(chunk);
+        Ok(chunk.len())
+    })
+    .unwrap();
+
+    let headers = data.clone();
+    easy.header_function(move |header| {
+        headers
+            .lock()
+            .unwrap()
+            .headers
+            .push(Response::head_line(header));
+        true
+    })
+    .unwrap();
+}
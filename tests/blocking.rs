@@ -0,0 +1,144 @@
+mod support;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chipp_http::blocking::HttpClient;
+use chipp_http::mock::MockTransport;
+use chipp_http::{Body, ErrorKind, ExponentialBackoff, HttpMethod, TimeoutPhase};
+use serde::Deserialize;
+use support::{Response, Server};
+
+#[derive(Deserialize)]
+struct Echo {
+    args: std::collections::HashMap<String, String>,
+    headers: std::collections::HashMap<String, String>,
+    method: String,
+    data: String,
+}
+
+#[test]
+fn test_get_json() {
+    let httpbin = support::httpbin::server();
+
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
+    http_client.set_default_headers(&[("Authorization", "Bearer kek")]);
+
+    let echo: Echo = http_client
+        .get_with_params(["get"], &[("page", "2")])
+        .unwrap();
+
+    assert_eq!(echo.method, "GET");
+    assert_eq!(echo.args["page"], "2");
+    assert_eq!(echo.headers["Authorization"], "Bearer kek");
+}
+
+#[test]
+fn test_streamed_body() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let mut request = http_client.new_request(["anything"]);
+    request.set_method(HttpMethod::Put);
+    request.set_body(Body::from_reader(&b"streamed body"[..], None));
+
+    let echo: Echo = http_client
+        .perform_request(request, chipp_http::json::parse_json)
+        .unwrap();
+
+    assert_eq!(echo.method, "PUT");
+    assert_eq!(echo.data, "streamed body");
+}
+
+#[test]
+fn test_http_error() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let request = http_client.new_request(["status", "404"]);
+    let error = http_client
+        .perform_request(request, chipp_http::parse_void)
+        .unwrap_err();
+
+    match error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.status_code, 404),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_retries() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = attempts.clone();
+    let server = Server::new(move |_| {
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            Response::new(503)
+        } else {
+            Response::new(200).with_body("ok")
+        }
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(ExponentialBackoff {
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let request = http_client.new_request(["flaky"]);
+    http_client
+        .perform_request(request, chipp_http::parse_void)
+        .unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_timeout() {
+    let httpbin = support::httpbin::server();
+
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
+    http_client.set_timeout(Duration::from_millis(200));
+
+    let request = http_client.new_request(["delay", "2"]);
+    let error = http_client
+        .perform_request(request, chipp_http::parse_void)
+        .unwrap_err();
+
+    match error.kind {
+        ErrorKind::Timeout { phase } => assert_eq!(phase, TimeoutPhase::Total),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_custom_transport() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/users").respond(200, "[]");
+
+    let mut http_client = HttpClient::new("http://api.test/").unwrap();
+    http_client.set_transport(mock.clone());
+
+    let users: Vec<String> = http_client.get(["users"]).unwrap();
+
+    assert!(users.is_empty());
+    mock.assert_all_met();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_runs_on_calling_thread() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let _: Echo = http_client.get(["get"]).unwrap();
+
+    let driver_threads = std::fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(|task| std::fs::read_to_string(task.unwrap().path().join("comm")).ok())
+        .filter(|name| name.trim() == "chipp_http")
+        .count();
+
+    assert_eq!(driver_threads, 0);
+}
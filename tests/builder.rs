@@ -0,0 +1,123 @@
+mod support;
+
+use std::time::Duration;
+
+use chipp_http::{ErrorKind, HttpClient};
+use futures_executor::block_on;
+use serde::{Deserialize, Serialize};
+use support::{Response, Server};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Echo {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    content_type: Option<String>,
+    body: String,
+}
+
+fn echo_server() -> Server {
+    Server::new(|request| {
+        let echo = Echo {
+            method: request.method.clone(),
+            path: request.path.clone(),
+            authorization: request.header("Authorization").map(str::to_string),
+            content_type: request.header("Content-Type").map(str::to_string),
+            body: String::from_utf8(request.body.clone()).unwrap(),
+        };
+
+        if request.path.starts_with("/missing") {
+            Response::new(404)
+        } else {
+            Response::new(200).with_body(serde_json::to_vec(&echo).unwrap())
+        }
+    })
+}
+
+#[test]
+fn test_post_json() {
+    #[derive(Serialize)]
+    struct User {
+        name: &'static str,
+    }
+
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let echo: Echo = block_on(
+        http_client
+            .post(["users"])
+            .query(&[("page", "2")])
+            .header("Authorization", "Bearer token")
+            .json_body(&User { name: "chipp" })
+            .timeout(Duration::from_secs(5))
+            .json(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        echo,
+        Echo {
+            method: "POST".to_string(),
+            path: "/users?page=2".to_string(),
+            authorization: Some("Bearer token".to_string()),
+            content_type: Some("application/json".to_string()),
+            body: r#"{"name":"chipp"}"#.to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_terminals() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let response = block_on(http_client.delete(["items", "1"]).send()).unwrap();
+    assert_eq!(response.status_code, 200);
+
+    let text = block_on(http_client.put(["items"]).body("raw").text()).unwrap();
+    assert!(text.contains(r#""body":"raw""#));
+
+    let bytes = block_on(http_client.patch(["items"]).bytes()).unwrap();
+    assert!(bytes.starts_with(b"{"));
+
+    block_on(http_client.head(["items"]).void()).unwrap();
+}
+
+#[test]
+fn test_every_method_has_a_verb() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let verbs = [
+        ("GET", http_client.fetch(["items"])),
+        ("POST", http_client.post(["items"])),
+        ("PUT", http_client.put(["items"])),
+        ("PATCH", http_client.patch(["items"])),
+        ("DELETE", http_client.delete(["items"])),
+        ("OPTIONS", http_client.options(["items"])),
+    ];
+
+    for (method, builder) in verbs {
+        let echo: Echo = block_on(builder.json()).unwrap();
+        assert_eq!(echo.method, method);
+    }
+
+    let response = block_on(http_client.head(["items"]).send()).unwrap();
+    assert_eq!(response.status_code, 200);
+}
+
+#[test]
+fn test_send_returns_error_statuses() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let response = block_on(http_client.post(["missing"]).send()).unwrap();
+    assert_eq!(response.status_code, 404);
+
+    let error = block_on(http_client.post(["missing"]).void()).unwrap_err();
+    match error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.status_code, 404),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
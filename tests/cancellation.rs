@@ -0,0 +1,128 @@
+mod support;
+
+use std::io::{BufReader, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chipp_http::HttpClient;
+use futures_channel::oneshot;
+use futures_executor::block_on;
+use futures_util::future::{self, Either};
+use support::Server;
+
+#[test]
+fn test_dropped_request_aborts_transfer() {
+    let (started_tx, started_rx) = oneshot::channel::<()>();
+    let (closed_tx, closed_rx) = mpsc::channel::<Duration>();
+
+    let started_tx = std::sync::Mutex::new(Some(started_tx));
+
+    let server = Server::raw(move |stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        support::read_request(&mut reader).unwrap();
+
+        let mut stream = stream;
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100000000\r\n\r\n")
+            .unwrap();
+
+        let chunk = [b'x'; 1024];
+        for _ in 0..16 {
+            stream.write_all(&chunk).unwrap();
+        }
+
+        let _ = started_tx.lock().unwrap().take().unwrap().send(());
+
+        let dropped_at = Instant::now();
+        while dropped_at.elapsed() < Duration::from_secs(5) {
+            if stream.write_all(&chunk).is_err() {
+                closed_tx.send(dropped_at.elapsed()).unwrap();
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    });
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+    let request = http_client.new_request(["stream"]);
+    let response = http_client.perform_request(request, |_, res| Ok(res.body.len()));
+
+    match block_on(future::select(Box::pin(response), started_rx)) {
+        Either::Left(_) => panic!("request finished before it was dropped"),
+        Either::Right((_, response)) => drop(response),
+    }
+
+    let closed_after = closed_rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("server kept sending data after the request was dropped");
+
+    assert!(closed_after < Duration::from_secs(2));
+}
+
+#[test]
+fn test_dropped_request_stops_retries() {
+    let (attempt_tx, attempt_rx) = oneshot::channel::<()>();
+    let attempt_tx = std::sync::Mutex::new(Some(attempt_tx));
+
+    let server = Server::raw(move |stream| {
+        drop(stream);
+
+        if let Some(tx) = attempt_tx.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+    });
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+    let mut request = http_client.new_request(["retry"]);
+    request.set_retry_count(5);
+
+    let response = http_client.perform_request(request, chipp_http::parse_void);
+
+    match block_on(future::select(Box::pin(response), attempt_rx)) {
+        Either::Left(_) => panic!("request finished before it was dropped"),
+        Either::Right((_, response)) => drop(response),
+    }
+
+    thread::sleep(Duration::from_millis(1500));
+
+    assert_eq!(server.connections(), 1);
+}
+
+#[test]
+fn test_dropped_idle_request_aborts_promptly() {
+    let (started_tx, started_rx) = oneshot::channel::<()>();
+    let (closed_tx, closed_rx) = mpsc::channel::<Instant>();
+
+    let started_tx = std::sync::Mutex::new(Some(started_tx));
+
+    // nothing is sent back, so only the dropped request can wake the driver
+    let server = Server::raw(move |stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        support::read_request(&mut reader).unwrap();
+
+        let _ = started_tx.lock().unwrap().take().unwrap().send(());
+
+        let _ = reader.read(&mut [0; 1]);
+        closed_tx.send(Instant::now()).unwrap();
+    });
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+    let request = http_client.new_request(["idle"]);
+    let response = http_client.perform_request(request, chipp_http::parse_void);
+
+    let dropped_at = match block_on(future::select(Box::pin(response), started_rx)) {
+        Either::Left(_) => panic!("request finished before it was dropped"),
+        Either::Right((_, response)) => {
+            drop(response);
+            Instant::now()
+        }
+    };
+
+    let closed_at = closed_rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("connection stayed open after the request was dropped");
+
+    assert!(closed_at.duration_since(dropped_at) < Duration::from_millis(500));
+}
@@ -0,0 +1,193 @@
+mod support;
+
+use std::fs;
+use std::path::PathBuf;
+
+use chipp_http::cassette::{Cassette, CassetteConfig, MatchRules};
+use chipp_http::{ErrorKind, HttpClient, HttpMethod, NoInterceptor};
+use futures_executor::block_on;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Echo {
+    method: String,
+    data: String,
+}
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("chipp_http_{}_{}.json", std::process::id(), name))
+}
+
+fn client(base_url: &str, cassette: &Cassette) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new(base_url).unwrap();
+    http_client.set_transport(cassette.clone());
+    http_client
+}
+
+#[test]
+fn test_record_and_replay() {
+    let path = cassette_path("record_and_replay");
+    let httpbin = support::httpbin::server();
+    let base_url = httpbin.url();
+
+    let recorder = Cassette::record(&path, HttpClient::new(&base_url).unwrap());
+    let http_client = client(&base_url, &recorder);
+
+    let echo: Echo = block_on(http_client.post(["anything", "users"]).body("kek").json()).unwrap();
+    assert_eq!(echo.method, "POST");
+    assert_eq!(echo.data, "kek");
+
+    let error = block_on(
+        http_client
+            .request(HttpMethod::Get, ["status", "404"])
+            .void(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error.kind, ErrorKind::HttpError(ref response) if response.status_code == 404)
+    );
+
+    assert_eq!(recorder.len(), 2);
+    drop(httpbin);
+
+    let replay = Cassette::replay(&path).unwrap();
+    assert!(!replay.is_recording());
+    let http_client = client(&base_url, &replay);
+
+    let echo: Echo = block_on(http_client.post(["anything", "users"]).body("kek").json()).unwrap();
+    assert_eq!(echo.method, "POST");
+    assert_eq!(echo.data, "kek");
+
+    let error = block_on(
+        http_client
+            .request(HttpMethod::Get, ["status", "404"])
+            .void(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error.kind, ErrorKind::HttpError(ref response) if response.status_code == 404)
+    );
+
+    let error = block_on(
+        http_client
+            .request(HttpMethod::Get, ["status", "404"])
+            .void(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error.kind, ErrorKind::Io(ref err) if err.kind() == std::io::ErrorKind::NotFound)
+    );
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_strict_and_lenient_replay() {
+    let path = cassette_path("strict_and_lenient");
+    let httpbin = support::httpbin::server();
+    let base_url = httpbin.url();
+
+    let recorder = Cassette::record(&path, HttpClient::new(&base_url).unwrap());
+    let http_client = client(&base_url, &recorder);
+    block_on(
+        http_client
+            .post(["anything"])
+            .header("X-Trace", "1")
+            .body("first")
+            .void(),
+    )
+    .unwrap();
+    drop(httpbin);
+
+    let strict = Cassette::replay(&path).unwrap();
+    let http_client = client(&base_url, &strict);
+    let error = block_on(
+        http_client
+            .post(["anything"])
+            .header("X-Trace", "2")
+            .body("second")
+            .void(),
+    )
+    .unwrap_err();
+    assert!(matches!(error.kind, ErrorKind::Io(_)));
+
+    let mut config = CassetteConfig::new();
+    config.set_matching(MatchRules::lenient());
+    let lenient = Cassette::replay_with(&path, config).unwrap();
+    let http_client = client(&base_url, &lenient);
+    let echo: Echo = block_on(
+        http_client
+            .post(["anything"])
+            .header("X-Trace", "2")
+            .body("second")
+            .json(),
+    )
+    .unwrap();
+    assert_eq!(echo.data, "first");
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_credentials_are_redacted() {
+    let path = cassette_path("redacted");
+    let httpbin = support::httpbin::server();
+    let base_url = httpbin.url();
+
+    let recorder = Cassette::record(&path, HttpClient::new(&base_url).unwrap());
+    let http_client = client(&base_url, &recorder);
+    block_on(
+        http_client
+            .request(HttpMethod::Get, ["status", "204"])
+            .header("Authorization", "Bearer hunter2")
+            .header("Cookie", "session=hunter2")
+            .void(),
+    )
+    .unwrap();
+    drop(httpbin);
+
+    let tape = fs::read_to_string(&path).unwrap();
+    assert!(!tape.contains("hunter2"));
+    assert!(tape.contains("Authorization"));
+
+    // replays still match whatever credentials the request carries
+    let replay = Cassette::replay(&path).unwrap();
+    block_on(
+        client(&base_url, &replay)
+            .request(HttpMethod::Get, ["status", "204"])
+            .header("Authorization", "Bearer rotated")
+            .header("Cookie", "session=rotated")
+            .void(),
+    )
+    .unwrap();
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_once() {
+    let path = cassette_path("once");
+    let httpbin = support::httpbin::server();
+    let base_url = httpbin.url();
+
+    let cassette = Cassette::once(&path, HttpClient::new(&base_url).unwrap()).unwrap();
+    assert!(cassette.is_recording());
+    block_on(
+        client(&base_url, &cassette)
+            .request(HttpMethod::Get, ["get"])
+            .void(),
+    )
+    .unwrap();
+    drop(httpbin);
+
+    let cassette = Cassette::once(&path, HttpClient::new(&base_url).unwrap()).unwrap();
+    assert!(!cassette.is_recording());
+    block_on(
+        client(&base_url, &cassette)
+            .request(HttpMethod::Get, ["get"])
+            .void(),
+    )
+    .unwrap();
+
+    fs::remove_file(&path).unwrap();
+}
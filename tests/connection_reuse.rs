@@ -0,0 +1,35 @@
+mod support;
+
+use chipp_http::HttpClient;
+use futures_executor::block_on;
+use support::{Response, Server};
+
+#[test]
+fn test_sequential_requests_reuse_connection() {
+    let server = Server::new(|_| Response::new(200).with_body("ok"));
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    for _ in 0..3 {
+        let request = http_client.new_request(["reuse"]);
+        block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+    }
+
+    assert_eq!(server.connections(), 1);
+}
+
+#[test]
+fn test_concurrent_requests() {
+    let server = Server::new(|request| Response::new(200).with_body(request.path.clone()));
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let requests = (0..10).map(|i| {
+        let request = http_client.new_request(["concurrent", &i.to_string()]);
+        http_client.perform_request(request, |_, response| Ok(response.body))
+    });
+
+    let bodies = block_on(futures_util::future::join_all(requests));
+
+    for (i, body) in bodies.into_iter().enumerate() {
+        assert_eq!(body.unwrap(), format!("/concurrent/{}", i).into_bytes());
+    }
+}
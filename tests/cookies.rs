@@ -0,0 +1,135 @@
+mod support;
+
+use std::collections::HashMap;
+use std::fs;
+
+use chipp_http::{Cookie, CookieJar, HttpClient, HttpMethod, NoInterceptor};
+use futures_executor::block_on;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Cookies {
+    cookies: HashMap<String, String>,
+}
+
+fn client_with_jar(base_url: &str, jar: &CookieJar) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new(base_url).unwrap();
+    http_client.set_cookie_jar(jar.clone());
+    http_client
+}
+
+#[test]
+fn test_session_cookies() {
+    let httpbin = support::httpbin::server();
+    let jar = CookieJar::new();
+    let http_client = client_with_jar(&httpbin.url(), &jar);
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["cookies", "set"])
+            .query(&[("session", "abc"), ("theme", "dark")])
+            .send(),
+    )
+    .unwrap();
+    assert_eq!(response.status_code, 302);
+
+    assert_eq!(jar.get("session").unwrap().value, "abc");
+
+    let echo: Cookies = block_on(http_client.get(["cookies"])).unwrap();
+    assert_eq!(echo.cookies["session"], "abc");
+    assert_eq!(echo.cookies["theme"], "dark");
+
+    block_on(
+        http_client
+            .request(HttpMethod::Get, ["cookies", "delete"])
+            .query(&[("theme", "")])
+            .send(),
+    )
+    .unwrap();
+
+    let echo: Cookies = block_on(http_client.get(["cookies"])).unwrap();
+    assert_eq!(echo.cookies.len(), 1);
+    assert!(jar.get("theme").is_none());
+}
+
+#[test]
+fn test_programmatic_cookies() {
+    let httpbin = support::httpbin::server();
+    let jar = CookieJar::new();
+    let http_client = client_with_jar(&httpbin.url(), &jar);
+
+    jar.insert(Cookie::new("token", "from-code", "127.0.0.1"));
+
+    let mut secure = Cookie::new("secret", "https-only", "127.0.0.1");
+    secure.secure = true;
+    jar.insert(secure);
+
+    let echo: Cookies = block_on(http_client.get(["cookies"])).unwrap();
+    assert_eq!(
+        echo.cookies,
+        HashMap::from([("token".to_string(), "from-code".to_string())])
+    );
+
+    let explicit = block_on(
+        http_client
+            .request(HttpMethod::Get, ["cookies"])
+            .header("Cookie", "explicit=1")
+            .json::<Cookies>(),
+    )
+    .unwrap();
+    assert_eq!(
+        explicit.cookies,
+        HashMap::from([("explicit".to_string(), "1".to_string())])
+    );
+}
+
+#[test]
+fn test_persistence() {
+    let httpbin = support::httpbin::server();
+    let jar = CookieJar::new();
+    let http_client = client_with_jar(&httpbin.url(), &jar);
+
+    block_on(
+        http_client
+            .request(HttpMethod::Get, ["cookies", "set"])
+            .query(&[("persisted", "yes")])
+            .send(),
+    )
+    .unwrap();
+
+    let dir = std::env::temp_dir();
+    let netscape = dir.join(format!("chipp_http_{}_cookies.txt", std::process::id()));
+    let json = dir.join(format!("chipp_http_{}_cookies.json", std::process::id()));
+
+    jar.save_netscape(&netscape).unwrap();
+    jar.save_json(&json).unwrap();
+
+    for loaded in [
+        CookieJar::load_netscape(&netscape).unwrap(),
+        CookieJar::load_json(&json).unwrap(),
+    ] {
+        let http_client = client_with_jar(&httpbin.url(), &loaded);
+        let echo: Cookies = block_on(http_client.get(["cookies"])).unwrap();
+        assert_eq!(echo.cookies["persisted"], "yes");
+    }
+
+    fs::remove_file(&netscape).unwrap();
+    fs::remove_file(&json).unwrap();
+}
+
+#[test]
+fn test_blocking_client() {
+    let httpbin = support::httpbin::server();
+    let jar = CookieJar::new();
+
+    let mut http_client = chipp_http::blocking::HttpClient::new(httpbin.url()).unwrap();
+    http_client.set_cookie_jar(jar.clone());
+
+    let request = http_client.new_request_with_params(["cookies", "set"], &[("blocking", "1")]);
+    http_client
+        .perform_request(request, |_, response| Ok(response))
+        .unwrap();
+
+    let echo: Cookies = http_client.get(["cookies"]).unwrap();
+    assert_eq!(echo.cookies["blocking"], "1");
+}
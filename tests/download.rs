@@ -0,0 +1,225 @@
+mod support;
+
+use std::fs;
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::Duration;
+
+use chipp_http::{ErrorKind, ExponentialBackoff, HttpClient, Progress};
+use futures_executor::block_on;
+use support::Server;
+
+type Requests = Arc<Mutex<Vec<support::Request>>>;
+
+fn content(version: u8) -> Vec<u8> {
+    (0..1000).map(|i| (i % 251) as u8 ^ version).collect()
+}
+
+fn destination(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chipp_http_{}_{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join("artifact.bin")
+}
+
+fn fast_backoff() -> ExponentialBackoff {
+    ExponentialBackoff {
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    }
+}
+
+fn respond(mut stream: TcpStream, status: &str, headers: &[String], body: &[u8], length: usize) {
+    write!(stream, "HTTP/1.1 {}\r\nConnection: close\r\n", status).unwrap();
+    for header in headers {
+        write!(stream, "{}\r\n", header).unwrap();
+    }
+    write!(stream, "Content-Length: {}\r\n\r\n", length).unwrap();
+
+    stream.write_all(body).unwrap();
+    stream.flush().unwrap();
+}
+
+// Answers a full request by sending the first `cut` bytes and dropping the
+// connection, and a ranged request with the rest of the body from `resume_version`.
+fn flaky_server(cut: usize, accept_ranges: bool, resume_version: u8) -> (Server, Requests) {
+    let requests: Requests = Arc::new(Mutex::new(vec![]));
+
+    let seen = requests.clone();
+    let server = Server::raw(move |stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let request = support::read_request(&mut reader).unwrap();
+
+        let range = request
+            .header("Range")
+            .and_then(|range| range.strip_prefix("bytes="))
+            .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+        let attempt = seen.lock().unwrap().len();
+        seen.lock().unwrap().push(request);
+
+        let mut headers = vec![];
+        if accept_ranges {
+            headers.push("Accept-Ranges: bytes".to_string());
+        }
+
+        match range {
+            None if attempt == 0 => {
+                headers.push("ETag: \"v0\"".to_string());
+                respond(stream, "200 OK", &headers, &content(0)[..cut], 1000);
+            }
+            None => {
+                let version = resume_version;
+                headers.push(format!("ETag: \"v{}\"", version));
+                respond(stream, "200 OK", &headers, &content(version), 1000);
+            }
+            Some(start) => {
+                let version = resume_version;
+                headers.push(format!("ETag: \"v{}\"", version));
+                headers.push(format!("Content-Range: bytes {}-999/1000", start));
+                let body = &content(version)[start..];
+                respond(stream, "206 Partial Content", &headers, body, body.len());
+            }
+        }
+    });
+
+    (server, requests)
+}
+
+#[test]
+fn test_download_resumes_with_range() {
+    let (server, requests) = flaky_server(400, true, 0);
+    let path = destination("resume");
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let progress = Arc::new(Mutex::new(vec![]));
+    let reported = progress.clone();
+
+    let request = http_client.new_request(["artifact.bin"]);
+    let response = block_on(
+        http_client
+            .download_with_progress(request, &path, move |p| reported.lock().unwrap().push(p)),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 206);
+    assert_eq!(fs::read(&path).unwrap(), content(0));
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].header("Range"), Some("bytes=400-"));
+    assert_eq!(requests[1].header("If-Range"), Some("\"v0\""));
+
+    assert_eq!(
+        progress.lock().unwrap().last(),
+        Some(&Progress {
+            downloaded: 1000,
+            download_total: Some(1000),
+            uploaded: 0,
+            upload_total: None,
+        })
+    );
+}
+
+#[test]
+fn test_download_restarts_when_resource_changed() {
+    let (server, requests) = flaky_server(400, true, 1);
+    let path = destination("changed");
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let request = http_client.new_request(["artifact.bin"]);
+    block_on(http_client.download(request, &path)).unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), content(1));
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[1].header("Range"), Some("bytes=400-"));
+    assert_eq!(requests[2].header("Range"), None);
+}
+
+#[test]
+fn test_download_without_range_support_starts_over() {
+    let (server, requests) = flaky_server(400, false, 0);
+    let path = destination("no_ranges");
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let request = http_client.new_request(["artifact.bin"]);
+    block_on(http_client.download(request, &path)).unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), content(0));
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].header("Range"), None);
+}
+
+#[test]
+fn test_download_error_leaves_no_file() {
+    let server = Server::new(|_| support::Response::new(404).with_body("missing"));
+    let path = destination("not_found");
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let request = http_client.new_request(["artifact.bin"]);
+    let error = block_on(http_client.download(request, &path)).unwrap_err();
+
+    match error.kind {
+        ErrorKind::HttpError(response) => {
+            assert_eq!(response.status_code, 404);
+            assert_eq!(response.body, b"missing");
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_download_keeps_caller_range() {
+    let (server, requests) = flaky_server(1000, true, 0);
+    let path = destination("caller_range");
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["artifact.bin"]);
+    request.set_header("Range", "bytes=600-");
+    let response = block_on(http_client.download(request, &path)).unwrap();
+
+    assert_eq!(response.status_code, 206);
+    assert_eq!(fs::read(&path).unwrap(), &content(0)[600..]);
+    assert_eq!(
+        requests.lock().unwrap()[0].header("Range"),
+        Some("bytes=600-")
+    );
+}
+
+#[test]
+fn test_concurrent_downloads_to_one_destination() {
+    // both requests are in flight before either is answered
+    let barrier = Arc::new(Barrier::new(2));
+    let server = Server::new(move |_| {
+        barrier.wait();
+        support::Response::new(200).with_body(content(0))
+    });
+    let path = destination("concurrent");
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let first = http_client.download(http_client.new_request(["artifact.bin"]), &path);
+    let second = http_client.download(http_client.new_request(["artifact.bin"]), &path);
+    let (first, second) = block_on(futures_util::future::join(first, second));
+
+    first.unwrap();
+    second.unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), content(0));
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+}
@@ -1,3 +1,5 @@
+mod support;
+
 use chipp_http::HttpClient;
 use futures_executor::block_on;
 
@@ -7,7 +9,9 @@ fn test_get() {
         location: String,
     }
 
-    let url = url::Url::parse("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+
+    let url = url::Url::parse(&httpbin.url()).unwrap();
     let http_client = HttpClient::new(url.as_ref()).unwrap();
 
     let params = vec![("Location", "test")];
@@ -17,20 +21,13 @@ fn test_get() {
     let response = block_on(http_client.perform_request::<Response, _>(
         request,
         |_request, response| {
-            let mut location = None;
-
-            for header in response.headers.iter() {
-                if header.starts_with("location: ") {
-                    let (_, value) = header.split_at("Location: ".len());
-                    location = Some(value);
-                    break;
-                }
-            }
+            let location = response
+                .headers
+                .get("Location")
+                .expect("Location header wasn't received");
 
             Ok(Response {
-                location: location
-                    .expect("Location header wasn't received")
-                    .to_string(),
+                location: location.to_string(),
             })
         },
     ))
@@ -38,3 +35,31 @@ fn test_get() {
 
     assert_eq!(response.location, "test");
 }
+
+#[test]
+fn test_non_utf8_header() {
+    use std::io::{BufReader, Write};
+
+    let server = support::Server::raw(|stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut stream = stream;
+
+        while support::read_request(&mut reader).is_some() {
+            let head = b"HTTP/1.1 200 OK\r\nX-Name: caf\xe9\r\nContent-Length: 2\r\n\r\nok";
+            if stream.write_all(head).is_err() {
+                return;
+            }
+        }
+    });
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    for _ in 0..2 {
+        let request = http_client.new_request(vec!["latin1"]);
+        let response =
+            block_on(http_client.perform_request(request, |_, response| Ok(response))).unwrap();
+
+        assert_eq!(response.header("X-Name"), Some("caf\u{fffd}"));
+        assert_eq!(response.body, b"ok");
+    }
+}
@@ -1,4 +1,6 @@
-use chipp_http::{HttpClient, Interceptor, Request};
+mod support;
+
+use chipp_http::{FnInterceptor, HttpClient, Interceptor, Request};
 use curl::easy::Auth;
 use futures_executor::block_on;
 use serde::Deserialize;
@@ -10,14 +12,16 @@ fn test_get() {
         url: String,
     }
 
-    let url = url::Url::parse("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+
+    let url = url::Url::parse(&httpbin.url()).unwrap();
     let http_client = HttpClient::new(url.as_ref()).unwrap();
 
     assert_eq!(
         block_on(http_client.get::<Response, _>(vec!["get"]))
             .unwrap()
             .url,
-        "https://httpbin.org/get"
+        format!("{}get", httpbin.url())
     );
 }
 
@@ -31,7 +35,9 @@ fn test_get_with_params() {
         args: HashMap<String, String>,
     }
 
-    let http_client = HttpClient::new("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
 
     let params = vec![("key1", "value1"), ("key2", "value2")];
     let response =
@@ -39,7 +45,7 @@ fn test_get_with_params() {
 
     assert_eq!(
         response.url,
-        "https://httpbin.org/get?key1=value1&key2=value2"
+        format!("{}get?key1=value1&key2=value2", httpbin.url())
     );
     assert_eq!(
         response.args,
@@ -61,16 +67,18 @@ fn test_interceptor() {
         user: String,
     }
 
-    let http_client = HttpClient::new("https://httpbin.org/")
-        .unwrap()
-        .with_interceptor(|easy: &mut curl::easy::Easy, _: &Request| {
+    let httpbin = support::httpbin::server();
+
+    let http_client = HttpClient::new(httpbin.url()).unwrap().with_interceptor(
+        |easy: &mut curl::easy::Easy, _: &Request| {
             let mut auth = Auth::new();
             auth.basic(true);
             easy.http_auth(&auth).unwrap();
 
             easy.username("me").unwrap();
             easy.password("secure").unwrap();
-        });
+        },
+    );
 
     let response =
         block_on(http_client.get::<Response, _>(vec!["basic-auth", "me", "secure"])).unwrap();
@@ -79,6 +87,33 @@ fn test_interceptor() {
     assert!(response.authenticated);
 }
 
+#[test]
+fn test_request_interceptor() {
+    #[derive(Deserialize)]
+    struct Response {
+        authenticated: bool,
+        user: String,
+    }
+
+    let httpbin = support::httpbin::server();
+
+    let http_client = HttpClient::new(httpbin.url())
+        .unwrap()
+        .with_interceptor(FnInterceptor(|request: &mut Request| {
+            request.set_header("Authorization", "Basic bWU6c2VjdXJl");
+            request
+                .url
+                .path_segments_mut()
+                .unwrap()
+                .extend(["me", "secure"]);
+        }));
+
+    let response = block_on(http_client.get::<Response, _>(vec!["basic-auth"])).unwrap();
+
+    assert_eq!(response.user, "me");
+    assert!(response.authenticated);
+}
+
 #[test]
 fn test_default_headers() {
     #[derive(Deserialize)]
@@ -86,7 +121,9 @@ fn test_default_headers() {
         headers: std::collections::HashMap<String, String>,
     }
 
-    let mut http_client = HttpClient::new("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
     http_client.set_default_headers(&[("Authorization", "Bearer kek")]);
 
     let response = block_on(http_client.get::<Response, _>(vec!["get"])).unwrap();
@@ -104,11 +141,13 @@ fn test_request_headers() {
         headers: std::collections::HashMap<String, String>,
     }
 
-    let mut http_client = HttpClient::new("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
     http_client.set_default_headers(&[("Authorization", "Bearer default")]);
 
     let mut request = http_client.new_request(["get"]);
-    request.headers = Some(vec![("X-Kek-Id".to_string(), "123".to_string())]);
+    request.add_header("X-Kek-Id", "123");
 
     let response =
         block_on(http_client.perform_request::<Response, _>(request, chipp_http::json::parse_json))
@@ -130,9 +169,7 @@ fn test_interceptor_headers() {
         fn modify(&self, _: &mut curl::easy::Easy, _: &Request) {}
 
         fn add_headers(&self, headers: &mut curl::easy::List, _: &Request) {
-            headers
-                .append(&format!("X-Interceptor: intercepted"))
-                .unwrap();
+            headers.append("X-Interceptor: intercepted").unwrap();
         }
     }
 
@@ -141,7 +178,9 @@ fn test_interceptor_headers() {
         headers: std::collections::HashMap<String, String>,
     }
 
-    let mut http_client = HttpClient::new("https://httpbin.org/")
+    let httpbin = support::httpbin::server();
+
+    let mut http_client = HttpClient::new(httpbin.url())
         .unwrap()
         .with_interceptor(Authenticator);
     http_client.set_default_headers(&[("Authorization", "Bearer default")]);
@@ -1,3 +1,5 @@
+mod support;
+
 use chipp_http::{ErrorKind, HttpClient, HttpMethod};
 use futures_executor::block_on;
 use serde::Deserialize;
@@ -7,12 +9,16 @@ fn test_404() {
     #[derive(Debug, Deserialize)]
     struct Response;
 
-    let http_client = HttpClient::new("https://httpbin.org/").unwrap();
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
     let error = block_on(http_client.get::<Response, _>(vec!["status", "404"])).unwrap_err();
 
     match &error.kind {
         ErrorKind::HttpError(response) => {
-            assert_eq!(error.request.url.as_str(), "https://httpbin.org/status/404");
+            assert_eq!(
+                error.request.url.as_str(),
+                format!("{}status/404", httpbin.url())
+            );
             assert_eq!(error.request.method, HttpMethod::Get);
             assert_eq!(response.status_code, 404)
         }
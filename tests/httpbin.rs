@@ -0,0 +1,97 @@
+mod support;
+
+use std::time::Duration;
+
+use chipp_http::{ErrorKind, HttpClient, HttpMethod, Request, TimeoutPhase};
+use futures_executor::block_on;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Echo {
+    url: String,
+    method: String,
+}
+
+#[test]
+fn test_status() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let error = block_on(http_client.delete(["status", "418"]).void()).unwrap_err();
+
+    match error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.status_code, 418),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_basic_auth_rejected() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["basic-auth", "me", "secure"])
+            .header("Authorization", "Basic bWU6d3Jvbmc=")
+            .send(),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 401);
+    assert_eq!(
+        response.header("WWW-Authenticate"),
+        Some("Basic realm=\"Fake Realm\"")
+    );
+}
+
+#[test]
+fn test_redirect() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap().with_interceptor(
+        |easy: &mut curl::easy::Easy, _: &Request| {
+            easy.follow_location(true).unwrap();
+        },
+    );
+
+    let echo: Echo = block_on(http_client.get(["redirect", "3"])).unwrap();
+
+    assert_eq!(echo.url, format!("{}get", httpbin.url()));
+    assert_eq!(echo.method, "GET");
+}
+
+#[test]
+fn test_delay() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let request = http_client.new_request(["delay", "0.1"]);
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+
+    let mut request = http_client.new_request(["delay", "2"]);
+    request.set_timeout(Duration::from_millis(200));
+
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+    match error.kind {
+        ErrorKind::Timeout { phase } => assert_eq!(phase, TimeoutPhase::Total),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_gzip() {
+    #[derive(Deserialize)]
+    struct Gzipped {
+        gzipped: bool,
+    }
+
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap().with_interceptor(
+        |easy: &mut curl::easy::Easy, _: &Request| {
+            easy.accept_encoding("gzip").unwrap();
+        },
+    );
+
+    let response: Gzipped = block_on(http_client.get(["gzip"])).unwrap();
+    assert!(response.gzipped);
+}
@@ -0,0 +1,112 @@
+mod support;
+
+use chipp_http::{ErrorKind, HttpClient, HttpMethod};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+fn echo_server() -> Server {
+    Server::new(|request| {
+        Response::new(200)
+            .with_header("X-Method", &request.method)
+            .with_body(format!("{} {}", request.method, request.body.len()))
+    })
+}
+
+fn perform(http_client: &HttpClient<chipp_http::NoInterceptor>, method: HttpMethod) -> String {
+    let mut request = http_client.new_request(["method"]);
+    request.set_method(method);
+    request.set_body(b"payload".to_vec());
+
+    block_on(
+        http_client.perform_request(request, |_, res| Ok(String::from_utf8(res.body).unwrap())),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_methods_with_body() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    assert_eq!(perform(&http_client, HttpMethod::Patch), "PATCH 7");
+    assert_eq!(perform(&http_client, HttpMethod::Propfind), "PROPFIND 7");
+    assert_eq!(perform(&http_client, HttpMethod::Options), "OPTIONS 7");
+    assert_eq!(
+        perform(&http_client, HttpMethod::Custom("REPORT".to_string())),
+        "REPORT 7"
+    );
+}
+
+#[test]
+fn test_methods_without_body() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["method"]);
+    request.set_method(HttpMethod::Mkcol);
+
+    let body = block_on(
+        http_client.perform_request(request, |_, res| Ok(String::from_utf8(res.body).unwrap())),
+    )
+    .unwrap();
+
+    assert_eq!(body, "MKCOL 0");
+}
+
+#[test]
+fn test_head() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["method"]);
+    request.set_method(HttpMethod::Head);
+
+    let response = block_on(http_client.perform_request(request, |_, res| Ok(res))).unwrap();
+
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.header("X-Method"), Some("HEAD"));
+    assert!(response.body.is_empty());
+}
+
+#[test]
+fn test_get_with_body() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    assert_eq!(perform(&http_client, HttpMethod::Get), "GET 7");
+    assert_eq!(perform(&http_client, HttpMethod::Delete), "DELETE 7");
+}
+
+#[test]
+fn test_head_with_body() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["method"]);
+    request.set_method(HttpMethod::Head);
+    request.set_body(b"payload".to_vec());
+
+    let error = block_on(http_client.perform_request(request, |_, res| Ok(res))).unwrap_err();
+
+    match &error.kind {
+        ErrorKind::BodyNotAllowed(method) => assert_eq!(*method, HttpMethod::Head),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    assert_eq!(server.connections(), 0);
+}
+
+#[test]
+fn test_method_injection_is_rejected() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["method"]);
+    request.set_method(HttpMethod::Custom(
+        "GET /other HTTP/1.1\r\nX-Injected: 1\r\nX-Pad:".to_string(),
+    ));
+
+    let error = block_on(http_client.perform_request(request, |_, res| Ok(res))).unwrap_err();
+
+    assert!(matches!(error.kind, ErrorKind::InvalidMethod(_)));
+    assert_eq!(server.connections(), 0);
+}
@@ -0,0 +1,375 @@
+mod support;
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::poll_fn;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chipp_http::mock::MockTransport;
+use chipp_http::{
+    BoxFuture, ErrorKind, HttpClient, HttpMethod, Middleware, Next, NoInterceptor, Request,
+    Response,
+};
+use futures_executor::block_on;
+use futures_util::StreamExt;
+use serde::Deserialize;
+
+struct Trace {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Trace {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(format!("> {}", self.name));
+            let outcome = next.run(request).await;
+            self.log.lock().unwrap().push(format!("< {}", self.name));
+            outcome
+        })
+    }
+}
+
+struct Auth(&'static str);
+
+impl Middleware for Auth {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        request.add_header("Authorization", format!("Bearer {}", self.0));
+        next.run(request)
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    responses: Mutex<HashMap<String, Response>>,
+}
+
+impl Middleware for Cache {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let key = request.url.to_string();
+
+            if let Some(response) = self.responses.lock().unwrap().get(&key) {
+                return Ok(response.clone());
+            }
+
+            let response = next.run(request).await?;
+            self.responses.lock().unwrap().insert(key, response.clone());
+
+            Ok(response)
+        })
+    }
+}
+
+struct RetryUnavailable(usize);
+
+impl Middleware for RetryUnavailable {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            let mut attempts = 1;
+
+            loop {
+                let response = next.run(request).await?;
+
+                if response.status_code != 503 || attempts > self.0 {
+                    return Ok(response);
+                }
+
+                attempts += 1;
+            }
+        })
+    }
+}
+
+fn mocked_client(mock: &MockTransport) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new("http://api.test/").unwrap();
+    http_client.set_transport(mock.clone());
+    http_client
+}
+
+#[test]
+fn test_layering_order() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/users")
+        .header("Authorization", "Bearer token")
+        .respond(200, "[]");
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let http_client = mocked_client(&mock)
+        .with_middleware(Trace {
+            name: "outer",
+            log: log.clone(),
+        })
+        .with_middleware(Auth("token"))
+        .with_middleware(Trace {
+            name: "inner",
+            log: log.clone(),
+        });
+
+    let body = block_on(http_client.request(HttpMethod::Get, ["users"]).text()).unwrap();
+
+    assert_eq!(body, "[]");
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["> outer", "> inner", "< inner", "< outer"]
+    );
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_short_circuit() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/config")
+        .times(1)
+        .respond(200, "cached");
+
+    let http_client = mocked_client(&mock).with_middleware(Cache::default());
+
+    for _ in 0..3 {
+        let body = block_on(http_client.request(HttpMethod::Get, ["config"]).text()).unwrap();
+        assert_eq!(body, "cached");
+    }
+
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_retry_resends_body() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Post, "/jobs")
+        .body("payload")
+        .times(2)
+        .respond(503, "");
+    mock.expect(HttpMethod::Post, "/jobs")
+        .body("payload")
+        .respond(201, "created");
+
+    let http_client = mocked_client(&mock).with_middleware(RetryUnavailable(2));
+
+    let body = block_on(http_client.post(["jobs"]).body("payload").text()).unwrap();
+
+    assert_eq!(body, "created");
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_errors_pass_through() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/down")
+        .respond_fn(|_| Err(ErrorKind::CurlError(chipp_http::curl::Error::new(7))));
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let http_client = mocked_client(&mock).with_middleware(Trace {
+        name: "trace",
+        log: log.clone(),
+    });
+
+    let error = block_on(http_client.request(HttpMethod::Get, ["down"]).send()).unwrap_err();
+
+    assert!(matches!(error.kind, ErrorKind::CurlError(_)));
+    assert_eq!(*log.lock().unwrap(), vec!["> trace", "< trace"]);
+}
+
+#[test]
+fn test_interceptor_runs_under_middleware() {
+    #[derive(Deserialize)]
+    struct Echo {
+        headers: BTreeMap<String, String>,
+    }
+
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url())
+        .unwrap()
+        .with_middleware(Auth("token"))
+        .with_interceptor(|easy: &mut chipp_http::curl::easy::Easy, _: &Request| {
+            easy.useragent("intercepted").unwrap();
+        });
+
+    let echo: Echo = block_on(http_client.get(["anything"])).unwrap();
+
+    assert_eq!(echo.headers["Authorization"], "Bearer token");
+    assert_eq!(echo.headers["User-Agent"], "intercepted");
+}
+
+struct Repeat {
+    urls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Repeat {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            self.urls.lock().unwrap().push(request.url.to_string());
+            next.run(request).await?;
+
+            self.urls.lock().unwrap().push(request.url.to_string());
+            next.run(request).await
+        })
+    }
+}
+
+#[test]
+fn test_every_entry_point_runs_middleware() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/artifact")
+        .times(3)
+        .respond(200, "contents");
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let trace = || Trace {
+        name: "trace",
+        log: log.clone(),
+    };
+
+    let http_client = mocked_client(&mock).with_middleware(trace());
+
+    let streaming = block_on(http_client.request(HttpMethod::Get, ["artifact"]).stream()).unwrap();
+    let chunks: Vec<_> = block_on(streaming.body.collect());
+    assert_eq!(
+        chunks.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+        vec![Bytes::from("contents")]
+    );
+
+    let path =
+        std::env::temp_dir().join(format!("chipp_http_{}_middleware.bin", std::process::id()));
+    let request = http_client.new_request(["artifact"]);
+    block_on(http_client.download(request, &path)).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"contents");
+    std::fs::remove_file(&path).unwrap();
+
+    let mut blocking = chipp_http::blocking::HttpClient::new("http://api.test/").unwrap();
+    blocking.set_transport(mock.clone());
+    blocking.add_middleware(trace());
+
+    let request = blocking.new_request(["artifact"]);
+    let body = blocking
+        .perform_request(request, |_, response| Ok(response.body))
+        .unwrap();
+    assert_eq!(body, b"contents");
+
+    assert_eq!(log.lock().unwrap().len(), 6);
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_interceptor_runs_once_per_dispatch() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/flaky")
+        .times(1)
+        .respond(503, "");
+    mock.expect(HttpMethod::Get, "/flaky")
+        .respond_fn(|request| {
+            Ok(Response {
+                status_code: 200,
+                body: request.url.query().unwrap_or_default().into(),
+                ..Default::default()
+            })
+        });
+
+    let mut http_client = HttpClient::new("http://api.test/")
+        .unwrap()
+        .with_middleware(RetryUnavailable(1))
+        .with_interceptor(chipp_http::FnInterceptor(|request: &mut Request| {
+            request.url.query_pairs_mut().append_pair("lang", "en");
+        }));
+    http_client.set_transport(mock.clone());
+
+    let query = block_on(http_client.request(HttpMethod::Get, ["flaky"]).text()).unwrap();
+
+    assert_eq!(query, "lang=en");
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_redirects_leave_the_request_alone() {
+    let httpbin = support::httpbin::server();
+
+    let urls = Arc::new(Mutex::new(vec![]));
+    let mut http_client = HttpClient::new(httpbin.url())
+        .unwrap()
+        .with_middleware(Repeat { urls: urls.clone() });
+    http_client.set_redirect_policy(chipp_http::RedirectPolicy::Limited(5));
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "1"])
+            .send(),
+    )
+    .unwrap();
+
+    let original = format!("{}redirect/1", httpbin.url());
+    assert_eq!(*urls.lock().unwrap(), vec![original.clone(), original]);
+    assert_eq!(
+        response.effective_url.unwrap().as_str(),
+        format!("{}get", httpbin.url())
+    );
+    assert_eq!(response.redirects.len(), 1);
+}
+
+// gives up on slow requests after polling the rest of the chain once, like a timeout would
+struct Impatient;
+
+impl Middleware for Impatient {
+    fn handle<'a>(
+        &'a self,
+        request: &'a mut Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<Response, ErrorKind>> {
+        Box::pin(async move {
+            if !request.url.path().starts_with("/delay") {
+                return next.run(request).await;
+            }
+
+            let mut run = next.run(request);
+
+            match poll_fn(|cx| Poll::Ready(run.as_mut().poll(cx))).await {
+                Poll::Ready(outcome) => outcome,
+                Poll::Pending => Err(ErrorKind::Io(std::io::ErrorKind::TimedOut.into())),
+            }
+        })
+    }
+}
+
+#[test]
+fn test_download_abandoned_by_middleware() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url())
+        .unwrap()
+        .with_middleware(Impatient);
+
+    let path =
+        std::env::temp_dir().join(format!("chipp_http_{}_abandoned.bin", std::process::id()));
+    let request = http_client.new_request(["delay", "1"]);
+    let error = block_on(http_client.download(request, &path)).unwrap_err();
+
+    assert!(
+        matches!(error.kind, ErrorKind::Io(ref err) if err.kind() == std::io::ErrorKind::TimedOut)
+    );
+    assert!(!path.exists());
+
+    // the transfer the middleware dropped must not take the driver down with it
+    std::thread::sleep(Duration::from_millis(1200));
+    let response = block_on(http_client.request(HttpMethod::Get, ["anything"]).send()).unwrap();
+    assert_eq!(response.status_code, 200);
+}
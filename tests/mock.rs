@@ -0,0 +1,193 @@
+use std::fs;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chipp_http::mock::MockTransport;
+use chipp_http::{
+    Body, ErrorKind, ExponentialBackoff, FnInterceptor, HttpClient, HttpMethod, Request, Response,
+};
+use futures_executor::block_on;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+fn mocked_client(mock: &MockTransport) -> HttpClient<chipp_http::NoInterceptor> {
+    let mut http_client = HttpClient::new("http://api.test/").unwrap();
+    http_client.set_transport(mock.clone());
+    http_client
+}
+
+#[test]
+fn test_mocked_json_roundtrip() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Post, "/users")
+        .query("notify", "true")
+        .header("Authorization", "Bearer token")
+        .json_body(&serde_json::json!({ "id": 0, "name": "kek" }))
+        .respond_json(
+            201,
+            &User {
+                id: 7,
+                name: "kek".to_string(),
+            },
+        );
+
+    let mut http_client = mocked_client(&mock);
+    http_client.set_default_headers(&[("Authorization", "Bearer token")]);
+
+    let user: User = block_on(
+        http_client
+            .post(["users"])
+            .query(&[("notify", "true")])
+            .json_body(&User {
+                id: 0,
+                name: "kek".to_string(),
+            })
+            .json(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "kek".to_string(),
+        }
+    );
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_mocked_retry() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/flaky")
+        .times(2)
+        .respond(503, "");
+    mock.expect(HttpMethod::Get, "/flaky").respond(200, "ok");
+
+    let mut http_client = mocked_client(&mock);
+    http_client.set_retry_policy(ExponentialBackoff {
+        initial_delay: Duration::from_millis(1),
+        ..Default::default()
+    });
+
+    let body = block_on(http_client.request(HttpMethod::Get, ["flaky"]).text()).unwrap();
+
+    assert_eq!(body, "ok");
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_mocked_transport_error() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/down")
+        .respond_fn(|_| Err(ErrorKind::CurlError(chipp_http::curl::Error::new(7))));
+
+    let http_client = mocked_client(&mock);
+    let error = block_on(http_client.request(HttpMethod::Get, ["down"]).send()).unwrap_err();
+
+    match error.kind {
+        ErrorKind::CurlError(err) => assert!(err.is_couldnt_connect()),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_mocked_stream_and_download() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/artifact")
+        .times(2)
+        .respond_with(Response {
+            status_code: 200,
+            body: b"artifact contents".to_vec(),
+            ..Default::default()
+        });
+
+    let http_client = mocked_client(&mock);
+
+    let streaming = block_on(http_client.request(HttpMethod::Get, ["artifact"]).stream()).unwrap();
+    let chunks: Vec<_> = block_on(streaming.body.collect());
+    let body: Vec<u8> = chunks
+        .into_iter()
+        .flat_map(|chunk| chunk.unwrap())
+        .collect();
+    assert_eq!(body, b"artifact contents");
+
+    let path = std::env::temp_dir().join(format!("chipp_http_{}_mocked.bin", std::process::id()));
+    let request = http_client.new_request(["artifact"]);
+    block_on(http_client.download(request, &path)).unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"artifact contents");
+    fs::remove_file(&path).unwrap();
+
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_mocked_async_body_fed_by_same_executor() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Post, "/upload")
+        .body("hello world")
+        .respond(200, "stored");
+
+    let http_client = mocked_client(&mock);
+
+    let (tx, rx) = futures_channel::mpsc::unbounded::<std::io::Result<Bytes>>();
+    let upload = http_client
+        .post(["upload"])
+        .body(Body::from_stream(rx, None))
+        .text();
+
+    // only runs once the upload has polled the body and yielded
+    let feed = async move {
+        tx.unbounded_send(Ok(Bytes::from("hello "))).unwrap();
+        tx.unbounded_send(Ok(Bytes::from("world"))).unwrap();
+    };
+
+    let (body, ()) = block_on(futures_util::future::join(upload, feed));
+
+    assert_eq!(body.unwrap(), "stored");
+    mock.assert_all_met();
+}
+
+#[test]
+fn test_mocked_request_interceptor() {
+    let mock = MockTransport::new();
+    mock.expect(HttpMethod::Get, "/me")
+        .query("lang", "en")
+        .header("Authorization", "Bearer token")
+        .respond(200, "ok");
+
+    let mut http_client = HttpClient::new("http://api.test/")
+        .unwrap()
+        .with_interceptor(FnInterceptor(|request: &mut Request| {
+            request.set_header("Authorization", "Bearer token");
+            request.url.query_pairs_mut().append_pair("lang", "en");
+        }));
+    http_client.set_transport(mock.clone());
+
+    let body = block_on(http_client.request(HttpMethod::Get, ["me"]).text()).unwrap();
+
+    assert_eq!(body, "ok");
+    mock.assert_all_met();
+}
+
+#[test]
+#[should_panic(expected = "unexpected request GET http://api.test/missing")]
+fn test_unmatched_request_fails_assertion() {
+    let mock = MockTransport::new();
+    let http_client = mocked_client(&mock);
+
+    let error = block_on(http_client.request(HttpMethod::Get, ["missing"]).void()).unwrap_err();
+    match &error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.status_code, 501),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    mock.assert_all_met();
+}
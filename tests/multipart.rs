@@ -0,0 +1,210 @@
+mod support;
+
+use std::fs;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chipp_http::{Body, ErrorKind, ExponentialBackoff, HttpClient, HttpMethod, Multipart, Part};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+type Received = Arc<Mutex<Vec<support::Request>>>;
+
+fn recording_server() -> (Server, Received) {
+    let received: Received = Arc::new(Mutex::new(vec![]));
+
+    let seen = received.clone();
+    let server = Server::new(move |request| {
+        seen.lock().unwrap().push(support::Request {
+            method: request.method.clone(),
+            path: request.path.clone(),
+            headers: request.headers.clone(),
+            body: request.body.clone(),
+        });
+        Response::new(200)
+    });
+
+    (server, received)
+}
+
+fn send(http_client: &HttpClient<chipp_http::NoInterceptor>, multipart: Multipart) {
+    let mut request = http_client.new_request(["upload"]);
+    request.set_method(HttpMethod::Post);
+    request.set_multipart(multipart);
+
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+}
+
+#[test]
+fn test_multipart_parts() {
+    let (server, received) = recording_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let path = std::env::temp_dir().join(format!("chipp_http_{}_report.csv", std::process::id()));
+    fs::write(&path, "a,b\n1,2\n").unwrap();
+
+    let multipart = Multipart::new()
+        .text("title", "quarterly")
+        .file("report", &path)
+        .unwrap()
+        .bytes("thumbnail", "thumb.png", vec![0x89, b'P', b'N', b'G'])
+        .part(
+            Part::text("meta", r#"{"draft":true}"#)
+                .content_type("application/json")
+                .header("X-Checksum", "abc"),
+        );
+    let boundary = multipart.boundary().to_string();
+    let length = multipart.len().unwrap();
+
+    send(&http_client, multipart);
+
+    let received = received.lock().unwrap();
+    let request = &received[0];
+
+    assert_eq!(
+        request.header("Content-Type"),
+        Some(format!("multipart/form-data; boundary={}", boundary).as_str())
+    );
+    assert_eq!(
+        request.header("Content-Length"),
+        Some(length.to_string().as_str())
+    );
+
+    let mut expected = format!(
+        "--{b}\r\n\
+         Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+         quarterly\r\n\
+         --{b}\r\n\
+         Content-Disposition: form-data; name=\"report\"; filename=\"{file}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n\
+         a,b\n1,2\n\r\n\
+         --{b}\r\n\
+         Content-Disposition: form-data; name=\"thumbnail\"; filename=\"thumb.png\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        b = boundary,
+        file = path.file_name().unwrap().to_str().unwrap()
+    )
+    .into_bytes();
+    expected.extend_from_slice(&[0x89, b'P', b'N', b'G']);
+    expected.extend_from_slice(
+        format!(
+            "\r\n--{b}\r\n\
+             Content-Disposition: form-data; name=\"meta\"\r\n\
+             Content-Type: application/json\r\n\
+             X-Checksum: abc\r\n\r\n\
+             {{\"draft\":true}}\r\n\
+             --{b}--\r\n",
+            b = boundary
+        )
+        .as_bytes(),
+    );
+
+    assert_eq!(request.body, expected);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_multipart_streamed_part() {
+    let (server, received) = recording_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let data = vec![b'z'; 256 * 1024];
+    let multipart = Multipart::new().part(
+        Part::body("blob", Body::from_reader(Cursor::new(data.clone()), None))
+            .file_name("blob.bin"),
+    );
+
+    send(&http_client, multipart);
+
+    let received = received.lock().unwrap();
+    let request = &received[0];
+
+    assert_eq!(request.header("Transfer-Encoding"), Some("chunked"));
+    assert!(request
+        .body
+        .windows(data.len())
+        .any(|window| window == data.as_slice()));
+}
+
+#[test]
+fn test_multipart_resent_on_retry() {
+    let received: Received = Arc::new(Mutex::new(vec![]));
+
+    let seen = received.clone();
+    let server = Server::new(move |request| {
+        let mut seen = seen.lock().unwrap();
+        seen.push(support::Request {
+            method: request.method.clone(),
+            path: request.path.clone(),
+            headers: request.headers.clone(),
+            body: request.body.clone(),
+        });
+
+        if seen.len() == 1 {
+            Response::new(503)
+        } else {
+            Response::new(200)
+        }
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(ExponentialBackoff {
+        initial_delay: Duration::from_millis(10),
+        retry_non_idempotent: true,
+        ..Default::default()
+    });
+
+    let path = std::env::temp_dir().join(format!("chipp_http_{}_retry.txt", std::process::id()));
+    fs::write(&path, "retry me").unwrap();
+
+    send(&http_client, Multipart::new().file("doc", &path).unwrap());
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].body, received[1].body);
+    assert!(String::from_utf8_lossy(&received[1].body).contains("retry me"));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_text_form() {
+    let (server, received) = recording_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    block_on(http_client.post(["form"]).form([("name", "value")]).void()).unwrap();
+
+    let received = received.lock().unwrap();
+    let body = String::from_utf8_lossy(&received[0].body);
+
+    assert!(body.contains("Content-Disposition: form-data; name=\"name\"\r\n\r\nvalue\r\n"));
+}
+
+#[test]
+fn test_part_header_injection_is_rejected() {
+    let (server, _) = recording_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let injected = [
+        Part::text("note", "hi").header("X-Note", "a\r\nX-Injected: yes"),
+        Part::text("note", "hi").content_type("text/plain\r\nX-Injected: yes"),
+    ];
+
+    for (part, name) in injected.into_iter().zip(["X-Note", "Content-Type"]) {
+        let mut request = http_client.new_request(["upload"]);
+        request.set_method(HttpMethod::Post);
+        request.set_multipart(Multipart::new().part(part));
+
+        let error =
+            block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+        match error.kind {
+            ErrorKind::InvalidHeader(header) => assert_eq!(header, name),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    assert_eq!(server.connections(), 0);
+}
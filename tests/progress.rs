@@ -0,0 +1,67 @@
+mod support;
+
+use std::sync::{Arc, Mutex};
+
+use chipp_http::{HttpClient, HttpMethod, Progress};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+const SIZE: usize = 512 * 1024;
+
+fn recorder() -> (Arc<Mutex<Vec<Progress>>>, impl Fn(Progress) + Send + Sync) {
+    let events = Arc::new(Mutex::new(vec![]));
+
+    let recorded = events.clone();
+    (events, move |progress| {
+        recorded.lock().unwrap().push(progress)
+    })
+}
+
+#[test]
+fn test_download_progress() {
+    let server = Server::new(|_| Response::new(200).with_body(vec![b'x'; SIZE]));
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let (events, hook) = recorder();
+    let body = block_on(
+        http_client
+            .request(HttpMethod::Get, ["big"])
+            .progress(hook)
+            .bytes(),
+    )
+    .unwrap();
+    assert_eq!(body.len(), SIZE);
+
+    let events = events.lock().unwrap();
+    assert!(!events.is_empty());
+    assert!(events
+        .windows(2)
+        .all(|pair| pair[0].downloaded <= pair[1].downloaded));
+
+    let last = events.last().unwrap();
+    assert_eq!(last.downloaded, SIZE as u64);
+    assert_eq!(last.download_total, Some(SIZE as u64));
+}
+
+#[test]
+fn test_upload_progress() {
+    let server =
+        Server::new(|request| Response::new(200).with_body(request.body.len().to_string()));
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let (events, hook) = recorder();
+    let body = block_on(
+        http_client
+            .post(["upload"])
+            .body(vec![b'y'; SIZE])
+            .progress(hook)
+            .text(),
+    )
+    .unwrap();
+    assert_eq!(body, SIZE.to_string());
+
+    let events = events.lock().unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.uploaded, SIZE as u64);
+    assert_eq!(last.upload_total, Some(SIZE as u64));
+}
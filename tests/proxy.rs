@@ -0,0 +1,134 @@
+mod support;
+
+use chipp_http::{blocking, HttpClient, HttpMethod, NoInterceptor, Proxy, ProxyConfig};
+use futures_executor::block_on;
+use serde::Deserialize;
+use serde_json::json;
+
+use support::{Response, Server};
+
+#[derive(Deserialize)]
+struct Proxied {
+    target: String,
+    authorization: Option<String>,
+}
+
+// Answers every request itself instead of forwarding it, reporting which
+// absolute URL it was asked for.
+fn proxy_server() -> Server {
+    Server::new(|request| {
+        let body = json!({
+            "target": request.path,
+            "authorization": request.header("Proxy-Authorization"),
+        });
+
+        Response::new(200)
+            .with_header("Content-Type", "application/json")
+            .with_body(body.to_string())
+    })
+}
+
+fn client(base_url: &str, proxy: ProxyConfig) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new(base_url).unwrap();
+    http_client.set_proxy(proxy);
+    http_client
+}
+
+#[test]
+fn test_requests_go_through_proxy() {
+    let proxy = proxy_server();
+    let http_client = client(
+        "http://service.invalid/api/",
+        ProxyConfig::new(Proxy::new(proxy.url()).unwrap()),
+    );
+
+    let proxied: Proxied = block_on(http_client.get(["items"])).unwrap();
+
+    assert_eq!(proxied.target, "http://service.invalid/api/items");
+    assert_eq!(proxied.authorization, None);
+}
+
+#[test]
+fn test_proxy_credentials() {
+    let proxy = proxy_server();
+    let http_client = client(
+        "http://service.invalid/",
+        ProxyConfig::new(
+            Proxy::new(proxy.url())
+                .unwrap()
+                .with_credentials("user", "p@ss"),
+        ),
+    );
+
+    let proxied: Proxied = block_on(http_client.get(["items"])).unwrap();
+
+    assert_eq!(proxied.authorization.unwrap(), "Basic dXNlcjpwQHNz");
+}
+
+#[test]
+fn test_no_proxy_bypass() {
+    let proxy = proxy_server();
+    let httpbin = support::httpbin::server();
+
+    let mut config = ProxyConfig::new(Proxy::new(proxy.url()).unwrap());
+    config.set_no_proxy("localhost, 127.0.0.0/8");
+    let http_client = client(&httpbin.url(), config);
+
+    let response = block_on(http_client.request(HttpMethod::Get, ["get"]).send()).unwrap();
+
+    assert_eq!(response.status_code, 200);
+    assert_eq!(proxy.connections(), 0);
+}
+
+#[test]
+fn test_per_scheme_proxies() {
+    let proxy = proxy_server();
+    let httpbin = support::httpbin::server();
+
+    let config = ProxyConfig {
+        https: Some(Proxy::new(proxy.url()).unwrap()),
+        ..Default::default()
+    };
+    let http_client = client(&httpbin.url(), config);
+
+    let response = block_on(http_client.request(HttpMethod::Get, ["get"]).send()).unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(proxy.connections(), 0);
+
+    let config = ProxyConfig {
+        http: Some(Proxy::new(proxy.url()).unwrap()),
+        ..Default::default()
+    };
+    let http_client = client("http://service.invalid/", config);
+
+    let proxied: Proxied = block_on(http_client.get(["items"])).unwrap();
+    assert_eq!(proxied.target, "http://service.invalid/items");
+}
+
+#[test]
+fn test_blocking_client_uses_proxy() {
+    let proxy = proxy_server();
+    let mut http_client = blocking::HttpClient::new("http://service.invalid/").unwrap();
+    http_client.set_proxy(ProxyConfig::new(Proxy::new(proxy.url()).unwrap()));
+
+    let proxied: Proxied = http_client.get(["items"]).unwrap();
+    assert_eq!(proxied.target, "http://service.invalid/items");
+}
+
+#[test]
+fn test_environment_is_opt_in() {
+    let proxy = proxy_server();
+    let httpbin = support::httpbin::server();
+
+    // the other tests configure proxies explicitly, which overrides this
+    std::env::set_var("http_proxy", proxy.url());
+
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+    let response = block_on(http_client.request(HttpMethod::Get, ["get"]).send()).unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(proxy.connections(), 0);
+
+    let http_client = client("http://service.invalid/", ProxyConfig::from_env());
+    let proxied: Proxied = block_on(http_client.get(["items"])).unwrap();
+    assert_eq!(proxied.target, "http://service.invalid/items");
+}
@@ -0,0 +1,286 @@
+mod support;
+
+use std::collections::HashMap;
+
+use chipp_http::curl::easy::List;
+use chipp_http::{
+    blocking, ErrorKind, HttpClient, HttpMethod, Interceptor, NoInterceptor, RedirectAttempt,
+    RedirectPolicy, Request,
+};
+use futures_executor::block_on;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Echo {
+    method: String,
+    url: String,
+    data: String,
+    headers: HashMap<String, String>,
+}
+
+fn client(base_url: &str, policy: RedirectPolicy) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new(base_url).unwrap();
+    http_client.set_redirect_policy(policy);
+    http_client
+}
+
+fn redirect_to(url: &str, status_code: u32) -> Vec<(String, String)> {
+    vec![
+        ("url".to_string(), url.to_string()),
+        ("status_code".to_string(), status_code.to_string()),
+    ]
+}
+
+#[test]
+fn test_follows_redirects_with_history() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(5));
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "3"])
+            .send(),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 200);
+    assert_eq!(
+        response
+            .redirects
+            .iter()
+            .map(|hop| (hop.url.path(), hop.status_code))
+            .collect::<Vec<_>>(),
+        vec![
+            ("/redirect/3", 302),
+            ("/redirect/2", 302),
+            ("/redirect/1", 302)
+        ]
+    );
+    assert_eq!(
+        response.effective_url.unwrap().as_str(),
+        format!("{}get", httpbin.url())
+    );
+}
+
+#[test]
+fn test_default_policy_does_not_follow() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "1"])
+            .send(),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 302);
+    assert!(response.redirects.is_empty());
+    assert_eq!(
+        response.effective_url.unwrap().as_str(),
+        format!("{}redirect/1", httpbin.url())
+    );
+}
+
+#[test]
+fn test_too_many_redirects() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(2));
+
+    let error = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "3"])
+            .send(),
+    )
+    .unwrap_err();
+
+    match &error.kind {
+        ErrorKind::TooManyRedirects(response) => {
+            assert_eq!(response.status_code, 302);
+            assert_eq!(response.redirects.len(), 2);
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_see_other_switches_to_get() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(5));
+
+    let echo: Echo = block_on(
+        http_client
+            .post(["redirect-to"])
+            .query(redirect_to("/anything/result", 303))
+            .header("Content-Type", "text/plain")
+            .body("payload")
+            .json(),
+    )
+    .unwrap();
+
+    assert_eq!(echo.method, "GET");
+    assert_eq!(echo.data, "");
+    assert!(!echo.headers.contains_key("Content-Type"));
+}
+
+#[test]
+fn test_temporary_redirect_keeps_method_and_body() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(5));
+
+    let echo: Echo = block_on(
+        http_client
+            .post(["redirect-to"])
+            .query(redirect_to("/anything/result", 307))
+            .body("payload")
+            .json(),
+    )
+    .unwrap();
+
+    assert_eq!(echo.method, "POST");
+    assert_eq!(echo.data, "payload");
+    assert_eq!(echo.url, format!("{}anything/result", httpbin.url()));
+}
+
+#[test]
+fn test_cross_origin_strips_authorization() {
+    let origin = support::httpbin::server();
+    let other = support::httpbin::server();
+    let http_client = client(&origin.url(), RedirectPolicy::Limited(5));
+
+    let echo: Echo = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect-to"])
+            .query(redirect_to(&format!("{}anything/same", origin.url()), 302))
+            .header("Authorization", "Bearer secret")
+            .json(),
+    )
+    .unwrap();
+    assert_eq!(echo.headers["Authorization"], "Bearer secret");
+
+    let echo: Echo = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect-to"])
+            .query(redirect_to(&format!("{}anything/other", other.url()), 302))
+            .header("Authorization", "Bearer secret")
+            .json(),
+    )
+    .unwrap();
+    assert_eq!(echo.url, format!("{}anything/other", other.url()));
+    assert!(!echo.headers.contains_key("Authorization"));
+}
+
+struct InjectCredentials;
+
+impl Interceptor for InjectCredentials {
+    fn add_headers(&self, headers: &mut List, _request: &Request) {
+        headers.append("Authorization: Bearer injected").unwrap();
+    }
+}
+
+#[test]
+fn test_cross_origin_skips_interceptor_headers() {
+    let origin = support::httpbin::server();
+    let other = support::httpbin::server();
+    let http_client =
+        client(&origin.url(), RedirectPolicy::Limited(5)).with_interceptor(InjectCredentials);
+
+    let echo: Echo = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect-to"])
+            .query(redirect_to(&format!("{}anything/same", origin.url()), 302))
+            .json(),
+    )
+    .unwrap();
+    assert_eq!(echo.headers["Authorization"], "Bearer injected");
+
+    let echo: Echo = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect-to"])
+            .query(redirect_to(&format!("{}anything/other", other.url()), 302))
+            .json(),
+    )
+    .unwrap();
+    assert_eq!(echo.url, format!("{}anything/other", other.url()));
+    assert!(!echo.headers.contains_key("Authorization"));
+}
+
+#[test]
+fn test_same_origin_policy() {
+    let origin = support::httpbin::server();
+    let other = support::httpbin::server();
+    let http_client = client(&origin.url(), RedirectPolicy::SameOrigin);
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect-to"])
+            .query(redirect_to(&format!("{}get", other.url()), 302))
+            .send(),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 302);
+    assert!(response.redirects.is_empty());
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "2"])
+            .send(),
+    )
+    .unwrap();
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.redirects.len(), 2);
+}
+
+#[test]
+fn test_request_policy_overrides_client() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(5));
+
+    let response = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "2"])
+            .redirect_policy(RedirectPolicy::custom(|attempt: &RedirectAttempt| {
+                attempt.history.is_empty()
+            }))
+            .send(),
+    )
+    .unwrap();
+
+    assert_eq!(response.status_code, 302);
+    assert_eq!(response.redirects.len(), 1);
+    assert_eq!(
+        response.effective_url.unwrap().as_str(),
+        format!("{}redirect/1", httpbin.url())
+    );
+}
+
+#[test]
+fn test_streaming_follows_redirects() {
+    let httpbin = support::httpbin::server();
+    let http_client = client(&httpbin.url(), RedirectPolicy::Limited(5));
+
+    let streaming = block_on(
+        http_client
+            .request(HttpMethod::Get, ["redirect", "2"])
+            .stream(),
+    )
+    .unwrap();
+
+    assert_eq!(streaming.response.status_code, 200);
+    assert_eq!(streaming.response.redirects.len(), 2);
+    assert_eq!(
+        streaming.response.effective_url.unwrap().as_str(),
+        format!("{}get", httpbin.url())
+    );
+}
+
+#[test]
+fn test_blocking_follows_redirects() {
+    let httpbin = support::httpbin::server();
+    let mut http_client = blocking::HttpClient::new(httpbin.url()).unwrap();
+    http_client.set_redirect_policy(RedirectPolicy::Limited(5));
+
+    let echo: Echo = http_client.get(["redirect", "2"]).unwrap();
+    assert_eq!(echo.url, format!("{}get", httpbin.url()));
+}
@@ -0,0 +1,186 @@
+mod support;
+
+use std::io::{self, Cursor, Read};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chipp_http::{Body, ExponentialBackoff, HttpClient, HttpMethod, NoInterceptor, Request};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+fn echo_server() -> Server {
+    Server::new(|request| {
+        let encoding = request.header("Transfer-Encoding").unwrap_or("identity");
+        Response::new(200)
+            .with_header("X-Encoding", encoding)
+            .with_body(request.body.clone())
+    })
+}
+
+fn upload(http_client: &HttpClient<NoInterceptor>, body: Body) -> (String, Vec<u8>) {
+    let mut request = http_client.new_request(["upload"]);
+    request.set_method(HttpMethod::Put);
+    request.set_body(body);
+
+    block_on(http_client.perform_request(request, |_, res| {
+        Ok((res.header("X-Encoding").unwrap().to_string(), res.body))
+    }))
+    .unwrap()
+}
+
+fn payload() -> Vec<u8> {
+    (0..200_000).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn test_reader_with_length() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let body = Body::from_reader(Cursor::new(payload()), Some(payload().len() as u64));
+    let (encoding, received) = upload(&http_client, body);
+
+    assert_eq!(encoding, "identity");
+    assert_eq!(received, payload());
+}
+
+#[test]
+fn test_reader_without_length_is_chunked() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let body = Body::from_reader(Cursor::new(payload()), None);
+    let (encoding, received) = upload(&http_client, body);
+
+    assert_eq!(encoding, "chunked");
+    assert_eq!(received, payload());
+}
+
+#[test]
+fn test_async_reader() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let body = Body::from_async_reader(futures_util::io::Cursor::new(payload()), None);
+    let (_, received) = upload(&http_client, body);
+
+    assert_eq!(received, payload());
+}
+
+// stands in for a reader bound to a runtime, which panics when polled off its thread
+struct RuntimeBound {
+    runtime: thread::ThreadId,
+    inner: futures_util::io::Cursor<Vec<u8>>,
+}
+
+impl futures_util::io::AsyncRead for RuntimeBound {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        assert_eq!(thread::current().id(), self.runtime, "no reactor running");
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+#[test]
+fn test_async_reader_is_polled_by_the_caller() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let reader = RuntimeBound {
+        runtime: thread::current().id(),
+        inner: futures_util::io::Cursor::new(payload()),
+    };
+    let (_, received) = upload(&http_client, Body::from_async_reader(reader, None));
+
+    assert_eq!(received, payload());
+}
+
+#[test]
+fn test_stream_of_chunks() {
+    let server = echo_server();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let (tx, rx) = futures_channel::mpsc::unbounded::<io::Result<Bytes>>();
+
+    thread::spawn(move || {
+        for chunk in payload().chunks(10_000) {
+            thread::sleep(Duration::from_millis(5));
+            tx.unbounded_send(Ok(Bytes::copy_from_slice(chunk)))
+                .unwrap();
+        }
+    });
+
+    let (encoding, received) = upload(&http_client, Body::from_stream(rx, None));
+
+    assert_eq!(encoding, "chunked");
+    assert_eq!(received, payload());
+}
+
+fn flaky_echo_server(attempts: Arc<AtomicUsize>) -> Server {
+    Server::new(move |request| {
+        if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            Response::new(503)
+        } else {
+            Response::new(200).with_body(request.body.clone())
+        }
+    })
+}
+
+fn retried_upload(server: &Server, body: Body) -> Result<Vec<u8>, chipp_http::Error> {
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(ExponentialBackoff {
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    let mut request: Request = http_client.new_request(["upload"]);
+    request.set_method(HttpMethod::Put);
+    request.set_body(body);
+
+    block_on(http_client.perform_request(request, |req, res| {
+        if res.status_code == 200 {
+            Ok(res.body)
+        } else {
+            Err((req, res).into())
+        }
+    }))
+}
+
+#[test]
+fn test_seekable_body_is_rewound_on_retry() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = flaky_echo_server(attempts.clone());
+
+    let body = Body::from_seekable(Cursor::new(payload()), None).unwrap();
+    let received = retried_upload(&server, body).unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(received, payload());
+}
+
+#[test]
+fn test_consumed_reader_is_not_retried() {
+    struct Once(Cursor<Vec<u8>>);
+
+    impl Read for Once {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = flaky_echo_server(attempts.clone());
+
+    let body = Body::from_reader(Once(Cursor::new(payload())), None);
+    retried_upload(&server, body).unwrap_err();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
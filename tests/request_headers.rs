@@ -0,0 +1,98 @@
+mod support;
+
+use chipp_http::{ErrorKind, HttpClient, HttpMethod};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+fn echo_headers() -> Server {
+    Server::new(|request| {
+        let body = request
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}\n", name.to_lowercase(), value))
+            .collect::<String>();
+
+        Response::new(200).with_body(body)
+    })
+}
+
+fn received_headers(
+    http_client: &HttpClient<chipp_http::NoInterceptor>,
+    request: chipp_http::Request,
+) -> Vec<String> {
+    block_on(http_client.perform_request(request, |_, res| {
+        Ok(String::from_utf8(res.body)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_request_header_overrides_default() {
+    let server = echo_headers();
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_default_headers(&[("Authorization", "Bearer default"), ("X-Client", "test")]);
+
+    let mut request = http_client.new_request(["headers"]);
+    request.set_header("authorization", "Bearer request");
+
+    let headers = received_headers(&http_client, request);
+
+    let authorization: Vec<_> = headers
+        .iter()
+        .filter(|h| h.starts_with("authorization:"))
+        .collect();
+
+    assert_eq!(authorization, vec!["authorization: Bearer request"]);
+    assert!(headers.contains(&"x-client: test".to_string()));
+}
+
+#[test]
+fn test_unset_curl_default_header() {
+    let server = echo_headers();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["headers"]);
+    request.unset_header("Accept");
+
+    let headers = received_headers(&http_client, request);
+
+    assert!(!headers.iter().any(|h| h.starts_with("accept:")));
+}
+
+#[test]
+fn test_unset_expect() {
+    let server = echo_headers();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["headers"]);
+    request.set_method(HttpMethod::Post);
+    request.set_body(vec![0; 2 * 1024 * 1024]);
+    request.unset_header("Expect");
+
+    let headers = received_headers(&http_client, request);
+
+    assert!(!headers.iter().any(|h| h.starts_with("expect:")));
+}
+
+#[test]
+fn test_header_injection_is_rejected() {
+    let server = echo_headers();
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut request = http_client.new_request(["headers"]);
+    request.set_header("X-Name", "value\r\nX-Injected: yes");
+
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    match error.kind {
+        ErrorKind::InvalidHeader(name) => assert_eq!(name, "X-Name"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    assert_eq!(server.connections(), 0);
+}
@@ -0,0 +1,155 @@
+mod support;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chipp_http::{ErrorKind, ExponentialBackoff, HttpClient, HttpMethod};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+fn flaky_server(failures: usize) -> (Server, Arc<AtomicUsize>) {
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = attempts.clone();
+    let server = Server::new(move |_| {
+        if counter.fetch_add(1, Ordering::SeqCst) < failures {
+            Response::new(503)
+        } else {
+            Response::new(200).with_body("ok")
+        }
+    });
+
+    (server, attempts)
+}
+
+fn fast_backoff() -> ExponentialBackoff {
+    ExponentialBackoff {
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_retries_status_codes() {
+    let (server, attempts) = flaky_server(2);
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let request = http_client.new_request(["flaky"]);
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_gives_up_after_max_retries() {
+    let (server, attempts) = flaky_server(10);
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let request = http_client.new_request(["flaky"]);
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    match error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.status_code, 503),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn test_does_not_retry_post_by_default() {
+    let (server, attempts) = flaky_server(1);
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let mut request = http_client.new_request(["flaky"]);
+    request.set_method(HttpMethod::Post);
+
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_request_policy_overrides_client_policy() {
+    let (server, attempts) = flaky_server(1);
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let mut request = http_client.new_request(["flaky"]);
+    request.set_retry_policy(|_: &chipp_http::RetryContext| None);
+
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_honors_retry_after() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = attempts.clone();
+    let server = Server::new(move |_| {
+        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            Response::new(429).with_header("Retry-After", "1")
+        } else {
+            Response::new(200)
+        }
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let started = std::time::Instant::now();
+
+    let request = http_client.new_request(["limited"]);
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[test]
+fn test_final_error_carries_last_response() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = attempts.clone();
+    let server = Server::new(move |_| {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst);
+        Response::new(503)
+            .with_header("Retry-After", "0")
+            .with_body(format!("attempt {}", attempt))
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(ExponentialBackoff::new(2));
+
+    let request = http_client.new_request(["unavailable"]);
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    match error.kind {
+        ErrorKind::HttpError(response) => assert_eq!(response.body, b"attempt 2"),
+        _ => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_retries_streaming_requests() {
+    let (server, attempts) = flaky_server(2);
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_retry_policy(fast_backoff());
+
+    let request = http_client.new_request(["flaky"]);
+    let streaming = block_on(http_client.perform_streaming(request)).unwrap();
+
+    assert_eq!(streaming.response.status_code, 200);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
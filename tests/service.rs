@@ -0,0 +1,102 @@
+#![cfg(feature = "tower")]
+
+mod support;
+
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chipp_http::{HttpClient, Request};
+use futures_executor::block_on;
+use futures_util::task::noop_waker;
+use tower::{BoxError, Service, ServiceBuilder, ServiceExt};
+
+#[test]
+fn test_call_through_layers() {
+    let httpbin = support::httpbin::server();
+    let http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let url = http_client.new_request(["anything"]).url;
+    let service = ServiceBuilder::new()
+        .map_request(|mut request: Request| {
+            request.set_header("X-Layer", "tower");
+            request
+        })
+        .map_err(BoxError::from)
+        .service(http_client);
+
+    let response = block_on(service.oneshot(Request::new(url))).unwrap();
+
+    assert_eq!(response.status_code, 200);
+    let echo: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+    assert_eq!(echo["headers"]["X-Layer"], "tower");
+}
+
+#[test]
+fn test_non_success_is_a_response() {
+    let httpbin = support::httpbin::server();
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
+
+    let request = http_client.new_request(["status", "503"]);
+    let response = block_on(async {
+        let service = http_client.ready().await?;
+        service.call(request).await
+    })
+    .unwrap();
+
+    assert_eq!(response.status_code, 503);
+}
+
+#[test]
+fn test_poll_ready_reflects_capacity() {
+    let httpbin = support::httpbin::server();
+    let mut http_client = HttpClient::new(httpbin.url()).unwrap();
+    http_client.set_max_connections(1);
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    assert!(http_client.poll_ready(&mut cx).is_ready());
+
+    let slow = http_client.call(http_client.new_request(["delay", "0.5"]));
+    let handle = thread::spawn(move || block_on(slow));
+
+    let started = Instant::now();
+    while http_client.poll_ready(&mut cx).is_ready() {
+        assert!(started.elapsed() < Duration::from_secs(2));
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let response = handle.join().unwrap().unwrap();
+    assert_eq!(response.status_code, 200);
+
+    assert!(matches!(
+        http_client.poll_ready(&mut cx),
+        Poll::Ready(Ok(()))
+    ));
+}
+
+#[test]
+fn test_poll_ready_reserves_a_slot() {
+    let httpbin = support::httpbin::server();
+    let mut first = HttpClient::new(httpbin.url()).unwrap();
+    first.set_max_connections(1);
+    let mut second = first.clone();
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    // nothing is in flight yet, but the first caller already holds the only slot
+    assert!(first.poll_ready(&mut cx).is_ready());
+    assert!(first.poll_ready(&mut cx).is_ready());
+    assert!(second.poll_ready(&mut cx).is_pending());
+
+    let response = block_on(first.call(first.new_request(["get"]))).unwrap();
+    assert_eq!(response.status_code, 200);
+    assert!(second.poll_ready(&mut cx).is_ready());
+
+    // an unused reservation is given back when its service goes away
+    let mut third = first.clone();
+    assert!(first.poll_ready(&mut cx).is_pending());
+    drop(second);
+    assert!(third.poll_ready(&mut cx).is_ready());
+}
@@ -0,0 +1,122 @@
+mod support;
+
+use std::io::{BufReader, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chipp_http::{HttpClient, HttpMethod};
+use futures_executor::block_on;
+use futures_util::StreamExt;
+use support::Server;
+
+const CHUNK: usize = 64 * 1024;
+
+fn chunked_server(chunks: usize, delay: Duration, written: Arc<AtomicUsize>) -> Server {
+    Server::raw(move |stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        support::read_request(&mut reader).unwrap();
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nX-Stream: yes\r\nContent-Length: {}\r\n\r\n",
+            chunks * CHUNK
+        )
+        .unwrap();
+
+        let chunk = vec![b'x'; CHUNK];
+        for _ in 0..chunks {
+            if stream.write_all(&chunk).is_err() {
+                return;
+            }
+
+            written.fetch_add(CHUNK, Ordering::SeqCst);
+            thread::sleep(delay);
+        }
+    })
+}
+
+#[test]
+fn test_resolves_before_body_completes() {
+    let written = Arc::new(AtomicUsize::new(0));
+    let server = chunked_server(10, Duration::from_millis(50), written.clone());
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+    let request = http_client.new_request(["stream"]);
+
+    let streaming = block_on(http_client.perform_streaming(request)).unwrap();
+
+    assert_eq!(streaming.response.status_code, 200);
+    assert_eq!(streaming.response.header("X-Stream"), Some("yes"));
+    assert!(written.load(Ordering::SeqCst) < 10 * CHUNK);
+
+    let total =
+        block_on(streaming.body.fold(
+            0,
+            |total, chunk| async move { total + chunk.unwrap().len() },
+        ));
+
+    assert_eq!(total, 10 * CHUNK);
+}
+
+#[test]
+fn test_resolves_before_first_body_byte() {
+    let server = Server::raw(|stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        support::read_request(&mut reader).unwrap();
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nX-Stream: yes\r\nContent-Length: 4\r\n\r\n"
+        )
+        .unwrap();
+
+        thread::sleep(Duration::from_secs(2));
+        let _ = stream.write_all(b"late");
+    });
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+    let request = http_client.new_request(["stream"]);
+
+    let started = Instant::now();
+    let streaming = block_on(http_client.perform_streaming(request)).unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(streaming.response.status_code, 200);
+    assert_eq!(streaming.response.header("X-Stream"), Some("yes"));
+    assert_eq!(streaming.response.previous[0].status_code, 100);
+
+    let body: Vec<u8> = block_on(streaming.body.fold(vec![], |mut body, chunk| async move {
+        body.extend_from_slice(&chunk.unwrap());
+        body
+    }));
+
+    assert_eq!(body, b"late");
+    assert!(started.elapsed() >= Duration::from_secs(2));
+}
+
+#[test]
+fn test_pauses_slow_consumer() {
+    let chunks = 1024;
+
+    let written = Arc::new(AtomicUsize::new(0));
+    let server = chunked_server(chunks, Duration::ZERO, written.clone());
+
+    let http_client = HttpClient::new(server.url()).unwrap();
+
+    let mut streaming =
+        block_on(http_client.request(HttpMethod::Get, ["stream"]).stream()).unwrap();
+
+    thread::sleep(Duration::from_millis(500));
+    assert!(written.load(Ordering::SeqCst) < chunks * CHUNK / 2);
+
+    let mut total = 0;
+    while let Some(chunk) = block_on(streaming.body.next()) {
+        total += chunk.unwrap().len();
+    }
+
+    assert_eq!(total, chunks * CHUNK);
+}
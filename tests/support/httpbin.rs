@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+
+use super::{Request, Response, Server};
+
+// A subset of https://httpbin.org served from localhost so that tests don't
+// depend on the network.
+pub fn server() -> Server {
+    Server::new(handle)
+}
+
+fn handle(request: &Request) -> Response {
+    let (path, query) = match request.path.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (request.path.as_str(), ""),
+    };
+
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+    match segments.as_slice() {
+        ["get"] | ["anything", ..] => json_response(200, echo(request)),
+        ["status", code] => match code.parse() {
+            Ok(code) => Response::new(code),
+            Err(_) => Response::new(400).with_body("invalid status code"),
+        },
+        ["basic-auth", user, password] => basic_auth(request, user, password),
+        ["response-headers"] => response_headers(query),
+        ["redirect", hops] => redirect(hops),
+        ["redirect-to"] => redirect_to(query),
+        ["delay", seconds] => {
+            let seconds: f64 = seconds.parse().unwrap_or(0.0);
+            thread::sleep(Duration::from_secs_f64(seconds.min(10.0)));
+            json_response(200, echo(request))
+        }
+        ["gzip"] => gzip(request),
+        ["cookies"] => json_response(200, json!({ "cookies": cookies(request) })),
+        ["cookies", "set"] => {
+            set_cookies(query, |name, value| format!("{}={}; Path=/", name, value))
+        }
+        ["cookies", "delete"] => set_cookies(query, |name, _| {
+            format!(
+                "{}=; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0; Path=/",
+                name
+            )
+        }),
+        _ => Response::new(404).with_body("not found"),
+    }
+}
+
+fn echo(request: &Request) -> Value {
+    let query = request.path.split_once('?').map_or("", |(_, query)| query);
+
+    json!({
+        "args": args(query),
+        "headers": headers(request),
+        "method": request.method,
+        "origin": "127.0.0.1",
+        "url": url(request),
+        "data": String::from_utf8_lossy(&request.body),
+    })
+}
+
+fn args(query: &str) -> BTreeMap<String, String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn headers(request: &Request) -> BTreeMap<String, String> {
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+
+    for (name, value) in &request.headers {
+        headers
+            .entry(title_case(name))
+            .and_modify(|existing| {
+                existing.push(',');
+                existing.push_str(value);
+            })
+            .or_insert_with(|| value.clone());
+    }
+
+    headers
+}
+
+fn url(request: &Request) -> String {
+    format!(
+        "http://{}{}",
+        request.header("Host").unwrap_or("127.0.0.1"),
+        request.path
+    )
+}
+
+fn basic_auth(request: &Request, user: &str, password: &str) -> Response {
+    let expected = format!(
+        "Basic {}",
+        base64(format!("{}:{}", user, password).as_bytes())
+    );
+
+    if request.header("Authorization") == Some(expected.as_str()) {
+        json_response(200, json!({ "authenticated": true, "user": user }))
+    } else {
+        Response::new(401).with_header("WWW-Authenticate", "Basic realm=\"Fake Realm\"")
+    }
+}
+
+fn response_headers(query: &str) -> Response {
+    let args = args(query);
+
+    let mut response = json_response(200, json!(args));
+    for (name, value) in &args {
+        response = response.with_header(name, value);
+    }
+
+    response
+}
+
+fn redirect(hops: &str) -> Response {
+    match hops.parse::<u32>() {
+        Ok(0) | Err(_) => Response::new(400).with_body("invalid redirect count"),
+        Ok(1) => Response::new(302).with_header("Location", "/get"),
+        Ok(hops) => Response::new(302).with_header("Location", &format!("/redirect/{}", hops - 1)),
+    }
+}
+
+fn redirect_to(query: &str) -> Response {
+    let args = args(query);
+
+    let Some(url) = args.get("url") else {
+        return Response::new(400).with_body("missing url");
+    };
+
+    let status = args
+        .get("status_code")
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(302);
+
+    Response::new(status).with_header("Location", url)
+}
+
+fn cookies(request: &Request) -> BTreeMap<String, String> {
+    request
+        .header("Cookie")
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn set_cookies<F: Fn(&str, &str) -> String>(query: &str, cookie: F) -> Response {
+    let mut response = Response::new(302).with_header("Location", "/cookies");
+
+    for (name, value) in args(query) {
+        response = response.with_header("Set-Cookie", &cookie(&name, &value));
+    }
+
+    response
+}
+
+fn gzip(request: &Request) -> Response {
+    let body = json!({
+        "gzipped": true,
+        "headers": headers(request),
+        "method": request.method,
+    });
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(body.to_string().as_bytes()).unwrap();
+
+    Response::new(200)
+        .with_header("Content-Type", "application/json")
+        .with_header("Content-Encoding", "gzip")
+        .with_body(encoder.finish().unwrap())
+}
+
+fn json_response(status: u32, body: Value) -> Response {
+    Response::new(status)
+        .with_header("Content-Type", "application/json")
+        .with_body(body.to_string())
+}
+
+fn title_case(name: &str) -> String {
+    name.split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::new();
+
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
@@ -0,0 +1,211 @@
+#![allow(dead_code)]
+
+pub mod httpbin;
+pub mod tls;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub struct Response {
+    pub status: u32,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u32) -> Response {
+        Response {
+            status,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Response {
+        self.body = body.into();
+        self
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Response + Send + Sync;
+
+pub struct Server {
+    addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Server {
+    pub fn new<F>(handler: F) -> Server
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let handler: Arc<Handler> = Arc::new(handler);
+        Server::raw(move |stream| serve(stream, handler.clone()))
+    }
+
+    pub fn raw<F>(handler: F) -> Server
+    where
+        F: Fn(TcpStream) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let connections = Arc::new(AtomicUsize::new(0));
+        let handler = Arc::new(handler);
+
+        let counter = connections.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let handler = handler.clone();
+                thread::spawn(move || handler(stream));
+            }
+        });
+
+        Server { addr, connections }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+fn serve(stream: TcpStream, handler: Arc<Handler>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+
+    while let Some(request) = read_request(&mut reader) {
+        let close = request
+            .header("Connection")
+            .is_some_and(|v| v.eq_ignore_ascii_case("close"));
+
+        let response = handler(&request);
+        if write_response(&mut writer, &request, &response).is_err() || close {
+            break;
+        }
+    }
+}
+
+pub fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: vec![],
+    };
+
+    let expects_continue = request
+        .header("Expect")
+        .is_some_and(|v| v.eq_ignore_ascii_case("100-continue"));
+
+    if expects_continue {
+        let mut stream = reader.get_ref();
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").ok()?;
+    }
+
+    let chunked = request
+        .header("Transfer-Encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"));
+
+    if chunked {
+        request.body = read_chunked(reader)?;
+    } else if let Some(length) = request.header("Content-Length") {
+        let mut body = vec![0; length.parse().ok()?];
+        reader.read_exact(&mut body).ok()?;
+        request.body = body;
+    }
+
+    Some(request)
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> Option<Vec<u8>> {
+    let mut body = vec![];
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+
+        let size = usize::from_str_radix(line.trim(), 16).ok()?;
+        let mut chunk = vec![0; size + 2];
+        reader.read_exact(&mut chunk).ok()?;
+
+        if size == 0 {
+            return Some(body);
+        }
+
+        body.extend_from_slice(&chunk[..size]);
+    }
+}
+
+fn write_response<W: Write>(
+    writer: &mut W,
+    request: &Request,
+    response: &Response,
+) -> std::io::Result<()> {
+    write!(writer, "HTTP/1.1 {} Status\r\n", response.status)?;
+
+    for (name, value) in &response.headers {
+        write!(writer, "{}: {}\r\n", name, value)?;
+    }
+
+    write!(writer, "Content-Length: {}\r\n\r\n", response.body.len())?;
+
+    if request.method != "HEAD" {
+        writer.write_all(&response.body)?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,226 @@
+use std::io::{BufRead, BufReader, Write};
+
+use openssl::asn1::Asn1Time;
+use openssl::base64;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::store::X509StoreBuilder;
+use openssl::x509::{X509Builder, X509NameBuilder, X509};
+
+pub use openssl::ssl::SslVersion;
+
+use super::Server;
+
+pub struct KeyPair {
+    pub cert: X509,
+    pub key: PKey<Private>,
+}
+
+impl KeyPair {
+    pub fn cert_pem(&self) -> Vec<u8> {
+        self.cert.to_pem().unwrap()
+    }
+
+    pub fn cert_der(&self) -> Vec<u8> {
+        self.cert.to_der().unwrap()
+    }
+
+    pub fn key_pem(&self) -> Vec<u8> {
+        self.key.private_key_to_pem_pkcs8().unwrap()
+    }
+
+    pub fn pkcs12(&self, password: &str) -> Vec<u8> {
+        Pkcs12::builder()
+            .name("client")
+            .pkey(&self.key)
+            .cert(&self.cert)
+            .build2(password)
+            .unwrap()
+            .to_der()
+            .unwrap()
+    }
+
+    // the pin format curl expects for CURLOPT_PINNEDPUBLICKEY
+    pub fn public_key_pin(&self) -> String {
+        let spki = self.key.public_key_to_der().unwrap();
+        let digest = hash(MessageDigest::sha256(), &spki).unwrap();
+        format!("sha256//{}", base64::encode_block(&digest))
+    }
+}
+
+// A throwaway CA issuing certificates for 127.0.0.1 and localhost.
+pub struct Authority {
+    pub root: KeyPair,
+}
+
+impl Authority {
+    pub fn new(name: &str) -> Authority {
+        let key = generate_key();
+        let mut builder = certificate_builder(name, &key);
+
+        builder.set_issuer_name(x509_name(name).as_ref()).unwrap();
+        builder
+            .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+            .unwrap();
+        builder
+            .append_extension(
+                KeyUsage::new()
+                    .critical()
+                    .key_cert_sign()
+                    .crl_sign()
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let key_id = SubjectKeyIdentifier::new()
+            .build(&builder.x509v3_context(None, None))
+            .unwrap();
+        builder.append_extension(key_id).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+        Authority {
+            root: KeyPair {
+                cert: builder.build(),
+                key,
+            },
+        }
+    }
+
+    pub fn issue(&self, name: &str) -> KeyPair {
+        let key = generate_key();
+        let mut builder = certificate_builder(name, &key);
+
+        builder
+            .set_issuer_name(self.root.cert.subject_name())
+            .unwrap();
+        builder
+            .append_extension(BasicConstraints::new().build().unwrap())
+            .unwrap();
+
+        let context = builder.x509v3_context(Some(&self.root.cert), None);
+        let alt_names = SubjectAlternativeName::new()
+            .dns("localhost")
+            .ip("127.0.0.1")
+            .build(&context)
+            .unwrap();
+        let authority_key_id = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&context)
+            .unwrap();
+        builder.append_extension(alt_names).unwrap();
+        builder.append_extension(authority_key_id).unwrap();
+        builder
+            .sign(&self.root.key, MessageDigest::sha256())
+            .unwrap();
+
+        KeyPair {
+            cert: builder.build(),
+            key,
+        }
+    }
+}
+
+fn generate_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+fn x509_name(name: &str) -> openssl::x509::X509Name {
+    let mut builder = X509NameBuilder::new().unwrap();
+    builder.append_entry_by_nid(Nid::COMMONNAME, name).unwrap();
+    builder.build()
+}
+
+fn certificate_builder(name: &str, key: &PKey<Private>) -> X509Builder {
+    let mut serial = BigNum::new().unwrap();
+    serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+
+    let mut builder = X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    builder
+        .set_serial_number(serial.to_asn1_integer().unwrap().as_ref())
+        .unwrap();
+    builder.set_subject_name(x509_name(name).as_ref()).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(Asn1Time::days_from_now(0).unwrap().as_ref())
+        .unwrap();
+    builder
+        .set_not_after(Asn1Time::days_from_now(1).unwrap().as_ref())
+        .unwrap();
+    builder
+}
+
+pub struct Options {
+    pub client_ca: Option<X509>,
+    pub max_version: Option<SslVersion>,
+}
+
+// Serves a single "200 OK" per connection whose body is the common name of
+// the client certificate, or "anonymous" without one.
+pub fn server(identity: &KeyPair, options: Options) -> Server {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_private_key(&identity.key).unwrap();
+    acceptor.set_certificate(&identity.cert).unwrap();
+
+    if let Some(max_version) = options.max_version {
+        acceptor.set_max_proto_version(Some(max_version)).unwrap();
+    }
+
+    if let Some(client_ca) = options.client_ca {
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(client_ca).unwrap();
+        acceptor.set_verify_cert_store(store.build()).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+
+    let acceptor = acceptor.build();
+
+    Server::raw(move |stream| {
+        let Ok(stream) = acceptor.accept(stream) else {
+            return;
+        };
+
+        let mut reader = BufReader::new(stream);
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return,
+                Ok(_) if line.trim_end().is_empty() => break,
+                Ok(_) => (),
+            }
+        }
+
+        let mut stream = reader.into_inner();
+        let client = stream
+            .ssl()
+            .peer_certificate()
+            .and_then(|cert| {
+                let entry = cert.subject_name().entries_by_nid(Nid::COMMONNAME).next()?;
+                String::from_utf8(entry.data().as_slice().to_vec()).ok()
+            })
+            .unwrap_or_else(|| "anonymous".to_string());
+
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            client.len(),
+            client
+        );
+        let _ = stream.flush();
+        let _ = stream.shutdown();
+    })
+}
+
+pub fn url(server: &Server) -> String {
+    server.url().replacen("http://", "https://", 1)
+}
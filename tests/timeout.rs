@@ -0,0 +1,100 @@
+mod support;
+
+use std::io::{BufReader, Write};
+use std::thread;
+use std::time::Duration;
+
+use chipp_http::{ErrorKind, HttpClient, TimeoutPhase};
+use futures_executor::block_on;
+use support::{Response, Server};
+
+fn assert_timeout(error: chipp_http::Error, expected: TimeoutPhase) {
+    match error.kind {
+        ErrorKind::Timeout { phase } => assert_eq!(phase, expected),
+        _ => panic!(
+            r#"assertion failed:
+expected: `ErrorKind::Timeout`
+     got: `{:?}`"#,
+            error
+        ),
+    }
+}
+
+#[test]
+fn test_total_timeout() {
+    let server = Server::new(|_| {
+        thread::sleep(Duration::from_millis(1000));
+        Response::new(200)
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_timeout(Duration::from_millis(200));
+
+    let request = http_client.new_request(["slow"]);
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    assert_timeout(error, TimeoutPhase::Total);
+}
+
+#[test]
+fn test_request_overrides_client_timeout() {
+    let server = Server::new(|_| {
+        thread::sleep(Duration::from_millis(300));
+        Response::new(200)
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_timeout(Duration::from_millis(100));
+
+    let mut request = http_client.new_request(["slow"]);
+    request.set_timeout(Duration::from_secs(5));
+
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+}
+
+#[test]
+fn test_low_speed_timeout() {
+    let server = Server::raw(|stream| {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        support::read_request(&mut reader).unwrap();
+
+        let mut stream = stream;
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n")
+            .unwrap();
+
+        thread::sleep(Duration::from_secs(5));
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_timeout(Duration::from_secs(10));
+    http_client.set_low_speed_timeout(1, Duration::from_secs(1));
+
+    let request = http_client.new_request(["stall"]);
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    assert_timeout(error, TimeoutPhase::LowSpeed);
+}
+
+#[test]
+fn test_timeout_on_reused_connection() {
+    let server = Server::new(|request| {
+        if request.path.ends_with("slow") {
+            thread::sleep(Duration::from_millis(1000));
+        }
+        Response::new(200)
+    });
+
+    let mut http_client = HttpClient::new(server.url()).unwrap();
+    http_client.set_timeout(Duration::from_millis(200));
+
+    let request = http_client.new_request(["fast"]);
+    block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap();
+
+    // curl reports a zero connect time for the reused connection
+    let request = http_client.new_request(["slow"]);
+    let error = block_on(http_client.perform_request(request, chipp_http::parse_void)).unwrap_err();
+
+    assert_eq!(server.connections(), 1);
+    assert_timeout(error, TimeoutPhase::Total);
+}
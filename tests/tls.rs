@@ -0,0 +1,147 @@
+mod support;
+
+use std::fs;
+
+use chipp_http::{
+    Certificate, Error, ErrorKind, HttpClient, HttpMethod, Identity, NoInterceptor, TlsConfig,
+    TlsVersion,
+};
+use futures_executor::block_on;
+
+use support::tls::{self, Authority, Options, SslVersion};
+
+// CURLE_SSL_PINNEDPUBKEYNOTMATCH
+const PINNED_KEY_MISMATCH: u32 = 90;
+
+fn client(server: &support::Server, config: Option<TlsConfig>) -> HttpClient<NoInterceptor> {
+    let mut http_client = HttpClient::new(tls::url(server)).unwrap();
+    if let Some(config) = config {
+        http_client.set_tls_config(config);
+    }
+    http_client
+}
+
+fn fetch(http_client: &HttpClient<NoInterceptor>) -> Result<String, Error> {
+    block_on(http_client.request(HttpMethod::Get, ["whoami"]).text())
+}
+
+fn curl_error(result: Result<String, Error>) -> chipp_http::curl::Error {
+    match result {
+        Err(Error {
+            kind: ErrorKind::CurlError(err),
+            ..
+        }) => err,
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(body) => panic!("unexpected success: {}", body),
+    }
+}
+
+fn trusting(authority: &Authority) -> TlsConfig {
+    let mut config = TlsConfig::new();
+    config.add_root_certificate(Certificate::from_pem(authority.root.cert_pem()));
+    config
+}
+
+fn plain_options() -> Options {
+    Options {
+        client_ca: None,
+        max_version: None,
+    }
+}
+
+#[test]
+fn test_private_authority_is_untrusted_by_default() {
+    let authority = Authority::new("Test Root");
+    let server = tls::server(&authority.issue("server"), plain_options());
+
+    let err = curl_error(fetch(&client(&server, None)));
+    assert!(err.is_peer_failed_verification(), "{:?}", err);
+}
+
+#[test]
+fn test_extra_root_certificates() {
+    let authority = Authority::new("Test Root");
+    let server = tls::server(&authority.issue("server"), plain_options());
+
+    let http_client = client(&server, Some(trusting(&authority)));
+    assert_eq!(fetch(&http_client).unwrap(), "anonymous");
+
+    let mut config = TlsConfig::new();
+    config.add_root_certificate(Certificate::from_der(&authority.root.cert_der()));
+    assert_eq!(fetch(&client(&server, Some(config))).unwrap(), "anonymous");
+
+    let path = std::env::temp_dir().join(format!("chipp_http_{}_root.der", std::process::id()));
+    fs::write(&path, authority.root.cert_der()).unwrap();
+    let certificate = Certificate::from_file(&path);
+    fs::remove_file(&path).unwrap();
+
+    let mut config = TlsConfig::new();
+    config.add_root_certificate(certificate.unwrap());
+    assert_eq!(fetch(&client(&server, Some(config))).unwrap(), "anonymous");
+}
+
+#[test]
+fn test_client_identity() {
+    let authority = Authority::new("Test Root");
+    let server = tls::server(
+        &authority.issue("server"),
+        Options {
+            client_ca: Some(authority.root.cert.clone()),
+            max_version: None,
+        },
+    );
+    let client_keys = authority.issue("service-a");
+
+    assert!(fetch(&client(&server, Some(trusting(&authority)))).is_err());
+
+    let mut config = trusting(&authority);
+    config.set_identity(Identity::from_pem(
+        client_keys.cert_pem(),
+        client_keys.key_pem(),
+    ));
+    assert_eq!(fetch(&client(&server, Some(config))).unwrap(), "service-a");
+
+    let mut config = trusting(&authority);
+    config.set_identity(Identity::from_pkcs12(
+        client_keys.pkcs12("secret"),
+        "secret",
+    ));
+    assert_eq!(fetch(&client(&server, Some(config))).unwrap(), "service-a");
+}
+
+#[test]
+fn test_min_version() {
+    let authority = Authority::new("Test Root");
+    let server = tls::server(
+        &authority.issue("server"),
+        Options {
+            client_ca: None,
+            max_version: Some(SslVersion::TLS1_2),
+        },
+    );
+
+    let mut config = trusting(&authority);
+    config.set_min_version(TlsVersion::Tls12);
+    assert!(fetch(&client(&server, Some(config))).is_ok());
+
+    let mut config = trusting(&authority);
+    config.set_min_version(TlsVersion::Tls13);
+    assert!(fetch(&client(&server, Some(config))).is_err());
+}
+
+#[test]
+fn test_public_key_pinning() {
+    let authority = Authority::new("Test Root");
+    let server_keys = authority.issue("server");
+    let server = tls::server(&server_keys, plain_options());
+
+    let mut config = trusting(&authority);
+    config.add_pinned_public_key(authority.issue("other").public_key_pin());
+    config.add_pinned_public_key(server_keys.public_key_pin());
+    assert_eq!(fetch(&client(&server, Some(config))).unwrap(), "anonymous");
+
+    let mut config = trusting(&authority);
+    config.add_pinned_public_key(authority.issue("other").public_key_pin());
+    let err = curl_error(fetch(&client(&server, Some(config))));
+    assert_eq!(err.code(), PINNED_KEY_MISMATCH);
+}
@@ -0,0 +1,55 @@
+mod support;
+
+use std::{env, fs};
+
+use chipp_http::{Certificate, ErrorKind, HttpClient, HttpMethod, NoInterceptor, TlsConfig};
+use futures_executor::block_on;
+
+use support::tls::{self, Authority, Options};
+
+// the default bundle comes from the environment, so this gets a process of its own
+#[test]
+fn test_extra_roots_need_the_default_bundle() {
+    let trusted = Authority::new("Bundled Root");
+    let extra = Authority::new("Extra Root");
+    let server = tls::server(
+        &trusted.issue("server"),
+        Options {
+            client_ca: None,
+            max_version: None,
+        },
+    );
+
+    let mut config = TlsConfig::new();
+    config.add_root_certificate(Certificate::from_pem(extra.root.cert_pem()));
+
+    let client = |config: &TlsConfig| {
+        let mut http_client = HttpClient::new(tls::url(&server)).unwrap();
+        http_client.set_tls_config(config.clone());
+        http_client
+    };
+    let fetch = |http_client: &HttpClient<NoInterceptor>| {
+        block_on(http_client.request(HttpMethod::Get, ["whoami"]).text())
+    };
+
+    let bundle = env::temp_dir().join(format!("chipp_http_{}_bundle.pem", std::process::id()));
+    fs::write(&bundle, trusted.root.cert_pem()).unwrap();
+    env::set_var("SSL_CERT_FILE", &bundle);
+
+    let http_client = client(&config);
+    let result = fetch(&http_client);
+    fs::remove_file(&bundle).unwrap();
+    assert_eq!(result.unwrap(), "anonymous");
+
+    // the bundle was read once, so the client keeps working without the file
+    assert_eq!(fetch(&http_client).unwrap(), "anonymous");
+
+    // a bundle that went missing is an error rather than a silently smaller trust store
+    let err = fetch(&client(&config)).unwrap_err();
+    assert!(
+        matches!(err.kind, ErrorKind::Io(ref err) if err.kind() == std::io::ErrorKind::NotFound),
+        "{:?}",
+        err
+    );
+    assert_eq!(server.connections(), 2);
+}